@@ -0,0 +1,326 @@
+//! Finding and loading assets, and the errors that can run into. Every asset is built into the
+//! binary, and a file with the same name in one of the directories of an `AssetPath` overrides it.
+//! Nothing here is fatal: whatever fails is reported and replaced by a fallback.
+
+
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{
+        read,
+        read_dir,
+    },
+    io::{
+        self,
+        ErrorKind,
+    },
+    path::PathBuf,
+    time::SystemTime,
+};
+use crate::{
+    svg_to_vector::{
+        normalize,
+        svg_to_vector,
+    },
+    Vec2,
+};
+
+
+/// Directory asteroid shapes are in, under each asset directory
+pub const SHAPE_DIR:&str="asteroids";
+/// Directory sounds are in, under each asset directory
+pub const SOUND_DIR:&str="sounds";
+/// Every asset built into the binary, by name relative to an asset directory
+const EMBEDDED:&[(&str,&[u8])]=&[
+    ("asteroids/asteroid1.svg",include_bytes!("../assets/asteroids/asteroid1.svg")),
+    ("asteroids/asteroid2.svg",include_bytes!("../assets/asteroids/asteroid2.svg")),
+    ("asteroids/asteroid3.svg",include_bytes!("../assets/asteroids/asteroid3.svg")),
+    ("sounds/asteroid_collision.wav",include_bytes!("../assets/sounds/asteroid_collision.wav")),
+    ("sounds/game_over.wav",include_bytes!("../assets/sounds/game_over.wav")),
+    ("sounds/hyperspace.wav",include_bytes!("../assets/sounds/hyperspace.wav")),
+    ("sounds/shield.wav",include_bytes!("../assets/sounds/shield.wav")),
+    ("sounds/shoot.wav",include_bytes!("../assets/sounds/shoot.wav")),
+];
+/// Outlines used when no asteroid shapes load at all, as closed polygons
+const BUILTIN_SHAPES:&[&[(f32,f32)]]=&[
+    &[
+        (-0.5,-1.0),(0.3,-1.0),(1.0,-0.5),(1.0,0.1),(0.5,0.3),(1.0,0.7),
+        (0.4,1.0),(-0.2,0.8),(-0.6,1.0),(-1.0,0.4),(-0.8,0.0),(-1.0,-0.5),
+    ],
+    &[
+        (-0.4,-1.0),(0.2,-0.8),(0.7,-1.0),(1.0,-0.4),(0.6,0.0),(1.0,0.5),
+        (0.5,1.0),(-0.3,0.9),(-0.9,1.0),(-1.0,0.2),(-0.7,-0.3),(-1.0,-0.6),
+    ],
+];
+
+
+/// Why an asset couldn't be used
+#[derive(Debug)]
+pub enum AssetError {
+    /// A directory of assets couldn't be listed
+    ReadDir{path:PathBuf,error:io::Error},
+    /// A file couldn't be opened or read
+    Read{path:PathBuf,error:io::Error},
+    /// An SVG file isn't valid or has nothing to draw
+    BadShape{path:PathBuf,reason:String},
+    /// A sound file couldn't be decoded
+    BadSound{path:PathBuf,reason:String},
+    /// Not a single asteroid shape loaded, so the built-in outlines are used
+    NoShapes{path:PathBuf},
+    /// There's no audio output to play on, so the game is silent
+    NoAudioOutput{reason:String},
+}
+impl fmt::Display for AssetError {
+    fn fmt(&self,f:&mut fmt::Formatter)->fmt::Result {
+        match self {
+            AssetError::ReadDir{path,error}=>write!(f,"{}: could not list directory: {}",path.display(),error),
+            AssetError::Read{path,error}=>write!(f,"{}: could not read: {}",path.display(),error),
+            AssetError::BadShape{path,reason}=>write!(f,"{}: invalid shape: {}",path.display(),reason),
+            AssetError::BadSound{path,reason}=>write!(f,"{}: invalid sound: {}",path.display(),reason),
+            AssetError::NoShapes{path}=>write!(f,"{}: no asteroid shapes, using the built-in outlines",path.display()),
+            AssetError::NoAudioOutput{reason}=>write!(f,"no audio output: {}, running without sound",reason),
+        }
+    }
+}
+impl std::error::Error for AssetError {}
+
+
+/// Somewhere an asset can be read from
+#[derive(Clone,Debug)]
+pub enum Source {
+    File(PathBuf),
+    /// The copy built into the binary, by name
+    Embedded(&'static str),
+}
+impl Source {
+    pub fn read(&self)->Result<Cow<'static,[u8]>,AssetError> {
+        match self {
+            Source::File(path)=>read(path)
+                .map(Cow::Owned)
+                .map_err(|error|AssetError::Read{path:path.clone(),error}),
+            Source::Embedded(name)=>Ok(Cow::Borrowed(embedded(name).unwrap_or_default())),
+        }
+    }
+    /// Path to report the asset by. Built-in assets are under `(built-in)`.
+    pub fn path(&self)->PathBuf {
+        match self {
+            Source::File(path)=>path.clone(),
+            Source::Embedded(name)=>PathBuf::from("(built-in)").join(name),
+        }
+    }
+}
+
+fn embedded(name:&str)->Option<&'static [u8]> {
+    EMBEDDED.iter()
+        .find(|(embedded_name,_)|*embedded_name==name)
+        .map(|(_,data)|*data)
+}
+
+
+/// Directories searched for assets, in order, before falling back to the built-in ones. Each has
+/// the same layout as `assets` in the source tree.
+#[derive(Clone,Debug,Default)]
+pub struct AssetPath {
+    pub dirs:Vec<PathBuf>,
+}
+impl AssetPath {
+    pub fn new(dirs:Vec<PathBuf>)->AssetPath {
+        AssetPath {
+            dirs,
+        }
+    }
+    /// Every place the asset `name` can be read from, best first: each directory that has it,
+    /// then the built-in copy if there is one
+    pub fn sources(&self,name:&str)->Vec<Source> {
+        let mut sources=self.dirs.iter()
+            .map(|dir|dir.join(name))
+            .filter(|path|path.is_file())
+            .map(Source::File)
+            .collect::<Vec<_>>();
+        if let Some(&(name,_))=EMBEDDED.iter().find(|(embedded_name,_)|*embedded_name==name) {
+            sources.push(Source::Embedded(name));
+        }
+        return sources;
+    }
+    /// Names of every asset in `dir` with the file extension `extension`, built in or not, sorted
+    pub fn list(&self,dir:&str,extension:&str,errors:&mut Vec<AssetError>)->Vec<String> {
+        let prefix=format!("{}/",dir);
+        let mut names=EMBEDDED.iter()
+            .map(|(name,_)|*name)
+            .filter(|name|name.starts_with(&prefix))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        for search_dir in self.dirs.iter() {
+            let path=search_dir.join(dir);
+            let entries=match read_dir(&path) {
+                Ok(entries)=>entries,
+                // Overrides are optional
+                Err(e) if e.kind()==ErrorKind::NotFound=>continue,
+                Err(error)=>{
+                    errors.push(AssetError::ReadDir{path,error});
+                    continue;
+                },
+            };
+            for entry in entries {
+                match entry {
+                    Ok(entry)=>{
+                        let file_name=entry.file_name();
+                        let file_name=file_name.to_string_lossy();
+                        if entry.path().extension().is_some_and(|ext|ext.eq_ignore_ascii_case(extension)) {
+                            names.push(format!("{}{}",prefix,file_name));
+                        }
+                    },
+                    Err(error)=>errors.push(AssetError::ReadDir{path:path.clone(),error}),
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        return names;
+    }
+    /// Loads the asset `name` from the first of its sources that `parse` accepts. The ones that
+    /// fail before that are added to `errors`.
+    pub fn load<T>(&self,name:&str,errors:&mut Vec<AssetError>,parse:impl Fn(&Source,Cow<'static,[u8]>)->Result<T,AssetError>)->Option<T> {
+        for source in self.sources(name) {
+            match source.read().and_then(|data|parse(&source,data)) {
+                Ok(asset)=>return Some(asset),
+                Err(e)=>errors.push(e),
+            }
+        }
+        return None;
+    }
+}
+
+
+/// Notices when the files in one asset directory change, by polling their modification times
+pub struct AssetWatcher {
+    dir:&'static str,
+    /// Every file in `dir` on the asset path, with when it was last modified
+    stamps:Vec<(PathBuf,Option<SystemTime>)>,
+}
+impl AssetWatcher {
+    /// Watches `dir`, like `SHAPE_DIR`, in every directory of `assets`
+    pub fn new(assets:&AssetPath,dir:&'static str)->AssetWatcher {
+        AssetWatcher {
+            dir,
+            stamps:stamps(assets,dir),
+        }
+    }
+    /// True if a file was added, removed or modified since the last call
+    pub fn poll(&mut self,assets:&AssetPath)->bool {
+        let stamps=stamps(assets,self.dir);
+        if stamps==self.stamps {
+            return false;
+        }
+        self.stamps=stamps;
+        return true;
+    }
+}
+
+fn stamps(assets:&AssetPath,dir:&str)->Vec<(PathBuf,Option<SystemTime>)> {
+    let mut stamps=Vec::new();
+    for search_dir in assets.dirs.iter() {
+        if let Ok(entries)=read_dir(search_dir.join(dir)) {
+            for entry in entries.flatten() {
+                let modified=entry.metadata().and_then(|metadata|metadata.modified()).ok();
+                stamps.push((entry.path(),modified));
+            }
+        }
+    }
+    stamps.sort();
+    return stamps;
+}
+
+
+/// Loads every asteroid shape on `assets`, in name order so a seed always picks the same shapes.
+/// Failures are added to `errors`, and if nothing loads the built-in outlines are used instead.
+pub fn load_asteroid_shapes(assets:&AssetPath,tolerance:f32,errors:&mut Vec<AssetError>)->Vec<Vec<Vec2>> {
+    let mut shapes=Vec::new();
+    for name in assets.list(SHAPE_DIR,"svg",errors) {
+        if let Some(shape)=assets.load(&name,errors,|source,data|parse_shape(source,&data,tolerance)) {
+            shapes.push(shape);
+        }
+    }
+    if shapes.is_empty() {
+        errors.push(AssetError::NoShapes{path:PathBuf::from(SHAPE_DIR)});
+        return builtin_shapes();
+    }
+    return shapes;
+}
+
+/// Reads an asteroid shape out of an SVG file
+pub fn parse_shape(source:&Source,data:&[u8],tolerance:f32)->Result<Vec<Vec2>,AssetError> {
+    let text=std::str::from_utf8(data).map_err(|e|e.to_string());
+    return text.and_then(|text|svg_to_vector(text,tolerance))
+        .map_err(|reason|AssetError::BadShape{path:source.path(),reason});
+}
+
+/// Outlines that work without any files at all, as line lists with a radius of 1
+pub fn builtin_shapes()->Vec<Vec<Vec2>> {
+    BUILTIN_SHAPES.iter()
+        .map(|points|{
+            let mut lines=Vec::new();
+            for (i,&(x,y)) in points.iter().enumerate() {
+                let (next_x,next_y)=points[(i+1)%points.len()];
+                lines.push(Vec2::new(x,y));
+                lines.push(Vec2::new(next_x,next_y));
+            }
+            normalize(&mut lines);
+            lines
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{
+        create_dir_all,
+        remove_dir_all,
+        write,
+    };
+    use super::*;
+
+    const TRIANGLE:&str=r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="M 0 0 L 10 0 L 5 10 Z"/></svg>"#;
+
+    fn embedded_shape(name:&'static str)->Vec<Vec2> {
+        let source=Source::Embedded(name);
+        return parse_shape(&source,&source.read().unwrap(),0.5).unwrap();
+    }
+
+    #[test]
+    fn overrides_win_and_broken_ones_fall_back() {
+        let dir=std::env::temp_dir().join(format!("asteroids-asset-test-{}",std::process::id()));
+        let shapes_dir=dir.join(SHAPE_DIR);
+        create_dir_all(&shapes_dir).unwrap();
+        write(shapes_dir.join("asteroid1.svg"),TRIANGLE).unwrap();
+        write(shapes_dir.join("asteroid2.svg"),"not an svg").unwrap();
+        write(shapes_dir.join("extra.svg"),TRIANGLE).unwrap();
+        let assets=AssetPath::new(vec![dir.clone()]);
+        let sources=assets.sources("asteroids/asteroid1.svg");
+        let mut errors=Vec::new();
+        let shapes=load_asteroid_shapes(&assets,0.5,&mut errors);
+        remove_dir_all(&dir).unwrap();
+        assert!(matches!(sources.as_slice(),[Source::File(_),Source::Embedded("asteroids/asteroid1.svg")]));
+        // The three built-in shapes and the extra one, in name order
+        assert_eq!(shapes.len(),4);
+        let triangle=svg_to_vector(TRIANGLE,0.5).unwrap();
+        assert_eq!(shapes[0],triangle);
+        assert_eq!(shapes[1],embedded_shape("asteroids/asteroid2.svg"));
+        assert_eq!(shapes[2],embedded_shape("asteroids/asteroid3.svg"));
+        assert_eq!(shapes[3],triangle);
+        assert_eq!(errors.len(),1);
+        match &errors[0] {
+            AssetError::BadShape{path,..}=>assert_eq!(path,&shapes_dir.join("asteroid2.svg")),
+            error=>panic!("unexpected error: {}",error),
+        }
+    }
+
+    #[test]
+    fn builtin_assets_load_without_any_directories() {
+        let mut errors=Vec::new();
+        let shapes=load_asteroid_shapes(&AssetPath::default(),0.5,&mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(shapes.len(),3);
+    }
+}
@@ -0,0 +1,144 @@
+use rodio::{
+    source::Buffered,
+    OutputStream,
+    OutputStreamHandle,
+    Sink,
+    Decoder,
+    Source,
+};
+use std::io::Cursor;
+use asteroids::{
+    asset::{
+        AssetError,
+        AssetPath,
+        SOUND_DIR,
+    },
+    Event,
+};
+
+
+type Sound=Buffered<Decoder<Cursor<Vec<u8>>>>;
+
+
+/// Owns the output device and the decoded sound effects, and plays them in response to game
+/// events. Sounds that didn't load, or everything if there's no output device, are skipped.
+pub struct Audio {
+    output:Option<Output>,
+    sounds:Sounds,
+}
+/// The decoded sound effects. Any that didn't load are `None`.
+struct Sounds {
+    collision:Option<Sound>,
+    shoot:Option<Sound>,
+    game_over:Option<Sound>,
+    hyperspace:Option<Sound>,
+    shield:Option<Sound>,
+}
+struct Output {
+    _stream:OutputStream,
+    stream_handle:OutputStreamHandle,
+    sinks:Vec<Sink>,
+    game_over_sink:Sink,
+}
+impl Audio {
+    /// Opens the default output device and loads the sounds from `assets`. Anything that fails is
+    /// added to `errors`.
+    pub fn new(assets:&AssetPath,errors:&mut Vec<AssetError>)->Audio {
+        let output=match Output::new() {
+            Ok(output)=>Some(output),
+            Err(reason)=>{
+                errors.push(AssetError::NoAudioOutput{reason});
+                None
+            },
+        };
+        Audio {
+            output,
+            sounds:Sounds::load(assets,errors),
+        }
+    }
+    /// Decodes the sounds again, for when their files change. Sounds already playing finish with
+    /// the old ones.
+    pub fn reload(&mut self,assets:&AssetPath,errors:&mut Vec<AssetError>) {
+        self.sounds=Sounds::load(assets,errors);
+    }
+    pub fn handle(&mut self,event:&Event) {
+        match event {
+            Event::Shot{..}|Event::SaucerShot{..}=>{
+                let sound=self.sounds.shoot.clone();
+                self.play(sound);
+            },
+            Event::AsteroidDestroyed{..}|Event::SaucerDestroyed{..}|Event::PlayerDied{..}=>{
+                let sound=self.sounds.collision.clone();
+                self.play(sound);
+            },
+            Event::Hyperspace{..}=>{
+                let sound=self.sounds.hyperspace.clone();
+                self.play(sound);
+            },
+            Event::ShieldRaised|Event::ShieldBounce{..}=>{
+                let sound=self.sounds.shield.clone();
+                self.play(sound);
+            },
+            Event::GameOver{..}=>{
+                if let (Some(output),Some(sound))=(&self.output,&self.sounds.game_over) {
+                    output.game_over_sink.append(sound.clone());
+                }
+            },
+            _=>{},
+        }
+    }
+    /// Plays the sound on the first idle sink, or a new one if they are all busy
+    fn play(&mut self,sound:Option<Sound>) {
+        let (output,sound)=match (&mut self.output,sound) {
+            (Some(output),Some(sound))=>(output,sound),
+            _=>return,
+        };
+        for sink in output.sinks.iter_mut() {
+            if sink.empty() {
+                sink.append(sound);
+                return;
+            }
+        }
+        if let Ok(sink)=Sink::try_new(&output.stream_handle) {
+            sink.append(sound);
+            output.sinks.push(sink);
+        }
+    }
+}
+impl Output {
+    fn new()->Result<Output,String> {
+        let (stream,stream_handle)=OutputStream::try_default().map_err(|e|e.to_string())?;
+        let sinks=vec![Sink::try_new(&stream_handle).map_err(|e|e.to_string())?];
+        let game_over_sink=Sink::try_new(&stream_handle).map_err(|e|e.to_string())?;
+        return Ok(Output {
+            _stream:stream,
+            stream_handle,
+            sinks,
+            game_over_sink,
+        });
+    }
+}
+
+
+impl Sounds {
+    fn load(assets:&AssetPath,errors:&mut Vec<AssetError>)->Sounds {
+        Sounds {
+            collision:load_sound(assets,"asteroid_collision.wav",errors),
+            shoot:load_sound(assets,"shoot.wav",errors),
+            game_over:load_sound(assets,"game_over.wav",errors),
+            hyperspace:load_sound(assets,"hyperspace.wav",errors),
+            shield:load_sound(assets,"shield.wav",errors),
+        }
+    }
+}
+
+
+/// Decodes the WAV file `name` from `assets`, adding to `errors` if it can't be
+fn load_sound(assets:&AssetPath,name:&str,errors:&mut Vec<AssetError>)->Option<Sound> {
+    assets.load(&format!("{}/{}",SOUND_DIR,name),errors,|source,data|{
+        match Decoder::new_wav(Cursor::new(data.into_owned())) {
+            Ok(decoder)=>Ok(decoder.buffered()),
+            Err(e)=>Err(AssetError::BadSound{path:source.path(),reason:e.to_string()}),
+        }
+    })
+}
@@ -0,0 +1,110 @@
+//! The simulation without a window or audio, for scripted runs on machines without the frontend's
+//! system libraries. Builds with `--no-default-features`.
+
+#![allow(clippy::needless_return)]
+
+
+use std::{
+    fs::read_to_string,
+    path::PathBuf,
+};
+use rand::{
+    Rng,
+    thread_rng,
+};
+use asteroids::{
+    asset::{
+        self,
+        AssetPath,
+    },
+    config::{
+        Config,
+        GameMode,
+    },
+    headless::{
+        self,
+        Script,
+    },
+    Game,
+};
+
+
+const USAGE:&str="Usage: asteroids-headless SECONDS [--seed SEED] [--mode classic|barrage] [--flight arcade|newtonian] [--set KEY=VALUE]... [--assets DIR]... [--script FILE]";
+
+
+/// Reads the command line into the run length, seed, config, asset path and script
+fn parse_args()->Result<(f32,Option<u64>,Config,AssetPath,Script),String> {
+    let mut seconds=None;
+    let mut seed=None;
+    let mut mode=GameMode::Classic;
+    let mut flight=None;
+    let mut overrides=Vec::new();
+    let mut asset_dirs=Vec::new();
+    let mut script=Script::new();
+    let mut args=std::env::args().skip(1);
+    while let Some(arg)=args.next() {
+        match arg.as_str() {
+            "--seed"=>{
+                let value=args.next().ok_or("--seed expects a number")?;
+                seed=Some(value.parse().map_err(|_|format!("Invalid seed: `{}`",value))?);
+            },
+            "--mode"=>{
+                mode=args.next().ok_or("--mode expects classic or barrage")?.parse()?;
+            },
+            "--flight"=>{
+                flight=Some(args.next().ok_or("--flight expects arcade or newtonian")?.parse()?);
+            },
+            "--set"=>{
+                overrides.push(args.next().ok_or("--set expects key=value")?);
+            },
+            "--assets"=>{
+                asset_dirs.push(PathBuf::from(args.next().ok_or("--assets expects a directory")?));
+            },
+            "--script"=>{
+                let path=args.next().ok_or("--script expects a file path")?;
+                let source=read_to_string(&path).map_err(|e|format!("Could not read `{}`: {}",path,e))?;
+                script=Script::parse(&source).map_err(|e|format!("{}: {}",path,e))?;
+            },
+            _ if seconds.is_none()=>{
+                seconds=Some(arg.parse().map_err(|_|format!("Invalid number of seconds: `{}`",arg))?);
+            },
+            _=>return Err(format!("Unknown argument: `{}`",arg)),
+        }
+    }
+    let seconds=seconds.ok_or("Missing the number of seconds to run")?;
+    let mut config=Config::for_mode(mode);
+    if let Some(flight)=flight {
+        config.flight=flight;
+    }
+    for set in overrides.iter() {
+        let value=toml::from_str(set).map_err(|e|format!("Invalid --set `{}`: {}",set,e))?;
+        config=config.merge(value).map_err(|e|format!("--set `{}`: {}",set,e))?;
+    }
+    return Ok((seconds,seed,config,AssetPath::new(asset_dirs),script));
+}
+
+
+fn main() {
+    let (seconds,seed,config,assets,script)=match parse_args() {
+        Ok(args)=>args,
+        Err(e)=>{
+            eprintln!("{}",e);
+            eprintln!("{}",USAGE);
+            std::process::exit(1);
+        },
+    };
+    let seed=seed.unwrap_or_else(||thread_rng().gen());
+    let mut errors=Vec::new();
+    let shapes=asset::load_asteroid_shapes(&assets,config.curve_tolerance,&mut errors);
+    for error in errors.iter() {
+        eprintln!("{}",error);
+    }
+    let mut game=match Game::with_shapes([1000.0,1000.0],seed,config,shapes) {
+        Ok(game)=>game,
+        Err(e)=>{
+            eprintln!("{}",e);
+            std::process::exit(1);
+        },
+    };
+    println!("{}",headless::run(&mut game,seconds,&script));
+}
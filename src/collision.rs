@@ -0,0 +1,116 @@
+//! Narrow phase collision tests on line lists, the same format the renderer draws: every two
+//! vertices are one segment.
+
+
+use crate::Vec2;
+
+
+/// Rotates, scales and moves a line list from model space into world space, in the same order
+/// the renderer does it
+pub fn transform(lines:&[Vec2],pos:Vec2,scale:Vec2,angle:f32)->Vec<Vec2> {
+    lines.iter()
+        .map(|vtx|vtx.rotated_z(angle)*scale+pos)
+        .collect()
+}
+
+/// Shortest offset from `from` to `to` in a world of `size` that wraps at its edges
+pub fn wrapped_offset(from:Vec2,to:Vec2,size:Vec2)->Vec2 {
+    let mut offset=to-from;
+    if offset.x>size.x/2.0 {
+        offset.x-=size.x;
+    } else if offset.x< -size.x/2.0 {
+        offset.x+=size.x;
+    }
+    if offset.y>size.y/2.0 {
+        offset.y-=size.y;
+    } else if offset.y< -size.y/2.0 {
+        offset.y+=size.y;
+    }
+    return offset;
+}
+
+/// Length of the longest vertex from the origin, for a bounding circle in model space
+pub fn extent(lines:&[Vec2])->f32 {
+    lines.iter()
+        .map(|vtx|vtx.magnitude())
+        .fold(0.0,f32::max)
+}
+
+/// True if the segment `a0`-`a1` crosses or touches `b0`-`b1`
+pub fn segments_intersect(a0:Vec2,a1:Vec2,b0:Vec2,b1:Vec2)->bool {
+    let da=a1-a0;
+    let db=b1-b0;
+    let denom=cross(da,db);
+    let diff=b0-a0;
+    if denom==0.0 {
+        // Parallel. Only colinear and overlapping segments touch.
+        if cross(diff,da)!=0.0 {return false}
+        let len_sq=da.magnitude_squared();
+        if len_sq==0.0 {return a0==b0}
+        let t0=diff.dot(da)/len_sq;
+        let t1=(b1-a0).dot(da)/len_sq;
+        return t0.min(t1)<=1.0&&t0.max(t1)>=0.0;
+    }
+    let t=cross(diff,db)/denom;
+    let u=cross(diff,da)/denom;
+    return (0.0..=1.0).contains(&t)&&(0.0..=1.0).contains(&u);
+}
+
+/// Even-odd test of `point` against a closed line list
+pub fn point_in_lines(point:Vec2,lines:&[Vec2])->bool {
+    let mut inside=false;
+    for seg in lines.chunks_exact(2) {
+        let (a,b)=(seg[0],seg[1]);
+        if (a.y>point.y)!=(b.y>point.y) {
+            let x=a.x+(point.y-a.y)/(b.y-a.y)*(b.x-a.x);
+            if point.x<x {
+                inside^=true;
+            }
+        }
+    }
+    return inside;
+}
+
+/// True if the segment `p0`-`p1` crosses any line, or starts inside the closed shape
+pub fn segment_hits_lines(p0:Vec2,p1:Vec2,lines:&[Vec2])->bool {
+    lines.chunks_exact(2).any(|seg|segments_intersect(p0,p1,seg[0],seg[1]))||point_in_lines(p0,lines)
+}
+
+/// True if any line of `a` crosses a line of `b`, or `a` is entirely inside the closed shape `b`.
+/// `a` does not have to be closed.
+pub fn lines_overlap(a:&[Vec2],b:&[Vec2])->bool {
+    let crossing=a.chunks_exact(2)
+        .any(|sa|b.chunks_exact(2).any(|sb|segments_intersect(sa[0],sa[1],sb[0],sb[1])));
+    return crossing||a.first().is_some_and(|p|point_in_lines(*p,b));
+}
+
+fn cross(a:Vec2,b:Vec2)->f32 {
+    a.x*b.y-a.y*b.x
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x:f32,y:f32)->Vec2 {Vec2::new(x,y)}
+
+    #[test]
+    fn crossing_segments_intersect() {
+        assert!(segments_intersect(v(0.0,0.0),v(2.0,2.0),v(0.0,2.0),v(2.0,0.0)));
+        assert!(!segments_intersect(v(0.0,0.0),v(1.0,1.0),v(3.0,0.0),v(2.0,1.0)));
+    }
+
+    #[test]
+    fn touching_segments_intersect() {
+        assert!(segments_intersect(v(0.0,0.0),v(1.0,0.0),v(1.0,0.0),v(1.0,1.0)));
+        assert!(segments_intersect(v(0.0,0.0),v(2.0,0.0),v(1.0,0.0),v(1.0,5.0)));
+    }
+
+    #[test]
+    fn parallel_segments_only_intersect_when_they_overlap() {
+        assert!(!segments_intersect(v(0.0,0.0),v(2.0,0.0),v(0.0,1.0),v(2.0,1.0)));
+        assert!(segments_intersect(v(0.0,0.0),v(2.0,0.0),v(1.0,0.0),v(3.0,0.0)));
+        assert!(!segments_intersect(v(0.0,0.0),v(1.0,0.0),v(2.0,0.0),v(3.0,0.0)));
+    }
+}
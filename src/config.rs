@@ -0,0 +1,228 @@
+//! Gameplay settings. They can be read from TOML on top of the defaults, which are the values the
+//! game has always been tuned with.
+
+
+use std::str::FromStr;
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use toml::Value;
+use crate::{
+    wave::Wave,
+    score::ScoreTable,
+    flight::FlightModel,
+};
+
+
+/// Named sets of rules a game can be started with
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum GameMode {
+    /// A handful of short range shots, like the arcade original
+    Classic,
+    /// More bullets that fly further
+    Barrage,
+}
+impl FromStr for GameMode {
+    type Err=String;
+    fn from_str(s:&str)->Result<GameMode,String> {
+        match s {
+            "classic"=>Ok(GameMode::Classic),
+            "barrage"=>Ok(GameMode::Barrage),
+            _=>Err(format!("Unknown game mode: `{}`",s)),
+        }
+    }
+}
+
+
+/// Gameplay settings that can change between games without a rebuild
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(default,deny_unknown_fields)]
+pub struct Config {
+    /// The player can't die. For testing.
+    pub disable_game_over:bool,
+    /// Speed of player bullets on top of the ship's own speed
+    pub bullet_speed:f32,
+    /// Seconds between player shots
+    pub shot_delay:f32,
+    /// Asteroids stop splitting while there are this many
+    pub max_asteroids:usize,
+    /// Largest radius of a new asteroid
+    pub asteroid_max_radius:f32,
+    /// Asteroids this small or smaller don't split
+    pub asteroid_min_radius:f32,
+    /// Smallest radius of a new asteroid
+    pub new_asteroid_min_radius:f32,
+    /// How much speed asteroids keep when they bounce off each other. 1 is perfectly elastic and
+    /// 0 makes them stop moving apart.
+    pub asteroid_restitution:f32,
+    /// Friction between touching asteroids. Glancing hits make them spin, and 0 turns that off.
+    pub asteroid_friction:f32,
+    /// Furthest a curve in an asteroid shape can be from the lines it's drawn with, in pixels at the
+    /// SVG's own size. At least 0.01, and curves are never split into more than
+    /// `svg_to_vector::MAX_CURVE_SEGMENTS` lines anyway.
+    pub curve_tolerance:f32,
+    /// Lives at the start of a game
+    pub lives:u32,
+    /// Seconds the player can't be hit after respawning
+    pub respawn_invulnerability:f32,
+    /// Difficulty curve, one entry per wave. Waves past the end get harder on their own.
+    pub waves:Vec<Wave>,
+    /// Seconds between clearing the field and the next wave
+    pub wave_delay:f32,
+    /// Average seconds between saucers
+    pub saucer_interval:f32,
+    /// Chance of a saucer being the small one that aims at the player
+    pub small_saucer_chance:f32,
+    /// Points for each asteroid size and saucer kind
+    pub scores:ScoreTable,
+    /// An extra life is awarded every time the score passes a multiple of this. 0 turns it off.
+    pub extra_life_every:u64,
+    /// Seconds between hyperspace jumps
+    pub hyperspace_cooldown:f32,
+    /// Chance of blowing up when arriving from hyperspace
+    pub hyperspace_failure_chance:f32,
+    /// Shield energy used per second while it's up, out of a full meter of 1
+    pub shield_drain:f32,
+    /// Shield energy recharged per second while it's down
+    pub shield_regen:f32,
+    /// Seconds a player bullet flies before it disappears
+    pub bullet_lifetime:f32,
+    /// Distance a player bullet flies before it disappears
+    pub bullet_range:f32,
+    /// Most player bullets on screen at once. Firing does nothing while this many are out, and a
+    /// spread shot only fires as many of its bullets as fit.
+    pub max_player_bullets:usize,
+    /// Chance of a power-up dropping from an asteroid the player shoots
+    pub powerup_chance:f32,
+    /// Seconds a timed power-up lasts once picked up
+    pub powerup_duration:f32,
+    /// How the player's ship handles
+    pub flight:FlightModel,
+}
+impl Config {
+    /// Reads `overrides` on top of this config and checks the result. Tables are merged key by key,
+    /// so anything `overrides` leaves out keeps its current value.
+    pub fn merge(&self,overrides:Value)->Result<Config,String> {
+        let mut value=Value::try_from(self).map_err(|e|e.to_string())?;
+        merge_values(&mut value,overrides);
+        let config:Config=value.try_into().map_err(|e|e.to_string())?;
+        config.validate()?;
+        return Ok(config);
+    }
+    /// Checks for values the game can't run with
+    pub fn validate(&self)->Result<(),String> {
+        let flight=&self.flight;
+        let checks=[
+            (self.bullet_speed>0.0,"`bullet_speed` must be above 0"),
+            (self.shot_delay>=0.0,"`shot_delay` can't be negative"),
+            (self.max_asteroids>=1,"`max_asteroids` must be at least 1"),
+            (self.asteroid_min_radius>0.0,"`asteroid_min_radius` must be above 0"),
+            (self.new_asteroid_min_radius>=self.asteroid_min_radius,"`new_asteroid_min_radius` can't be below `asteroid_min_radius`"),
+            (self.asteroid_max_radius>self.new_asteroid_min_radius,"`asteroid_max_radius` must be above `new_asteroid_min_radius`"),
+            ((0.0..=1.0).contains(&self.asteroid_restitution),"`asteroid_restitution` must be between 0 and 1"),
+            (self.asteroid_friction>=0.0,"`asteroid_friction` can't be negative"),
+            (self.curve_tolerance>=0.01,"`curve_tolerance` must be at least 0.01"),
+            (self.lives>=1,"`lives` must be at least 1"),
+            (self.respawn_invulnerability>=0.0,"`respawn_invulnerability` can't be negative"),
+            (self.wave_delay>=0.0,"`wave_delay` can't be negative"),
+            (self.saucer_interval>0.0,"`saucer_interval` must be above 0"),
+            ((0.0..=1.0).contains(&self.small_saucer_chance),"`small_saucer_chance` must be between 0 and 1"),
+            (self.hyperspace_cooldown>=0.0,"`hyperspace_cooldown` can't be negative"),
+            ((0.0..=1.0).contains(&self.hyperspace_failure_chance),"`hyperspace_failure_chance` must be between 0 and 1"),
+            (self.shield_drain>=0.0,"`shield_drain` can't be negative"),
+            (self.shield_regen>=0.0,"`shield_regen` can't be negative"),
+            (self.bullet_lifetime>0.0,"`bullet_lifetime` must be above 0"),
+            (self.bullet_range>0.0,"`bullet_range` must be above 0"),
+            (self.max_player_bullets>=1,"`max_player_bullets` must be at least 1"),
+            ((0.0..=1.0).contains(&self.powerup_chance),"`powerup_chance` must be between 0 and 1"),
+            (self.powerup_duration>0.0,"`powerup_duration` must be above 0"),
+            (flight.thrust>=0.0,"`flight.thrust` can't be negative"),
+            (flight.reverse_thrust>=0.0,"`flight.reverse_thrust` can't be negative"),
+            (flight.drag>=0.0,"`flight.drag` can't be negative"),
+            (flight.max_speed>0.0,"`flight.max_speed` must be above 0"),
+            (flight.rotate_speed>=0.0,"`flight.rotate_speed` can't be negative"),
+            (flight.rotate_accel>0.0,"`flight.rotate_accel` must be above 0"),
+        ];
+        for (ok,message) in checks {
+            if !ok {return Err(message.to_string())}
+        }
+        for (i,wave) in self.waves.iter().enumerate() {
+            if wave.asteroids==0 {
+                return Err(format!("`waves[{}].asteroids` must be at least 1",i));
+            }
+            if wave.speed.is_empty() {
+                return Err(format!("`waves[{}].speed` must have `start` below `end`",i));
+            }
+        }
+        return Ok(());
+    }
+    pub fn for_mode(mode:GameMode)->Config {
+        match mode {
+            GameMode::Classic=>Config::default(),
+            GameMode::Barrage=>Config {
+                bullet_lifetime:1.5,
+                bullet_range:1200.0,
+                max_player_bullets:10,
+                ..Config::default()
+            },
+        }
+    }
+}
+impl Default for Config {
+    fn default()->Config {
+        Config {
+            disable_game_over:false,
+            bullet_speed:1000.0,
+            shot_delay:0.1,
+            max_asteroids:50,
+            asteroid_max_radius:80.0,
+            asteroid_min_radius:30.0,
+            new_asteroid_min_radius:40.0,
+            asteroid_restitution:1.0,
+            asteroid_friction:0.2,
+            curve_tolerance:0.5,
+            lives:3,
+            respawn_invulnerability:3.0,
+            waves:vec![
+                Wave {asteroids:1,speed:50.0..200.0},
+                Wave {asteroids:2,speed:50.0..200.0},
+                Wave {asteroids:3,speed:60.0..220.0},
+                Wave {asteroids:4,speed:70.0..240.0},
+                Wave {asteroids:5,speed:80.0..260.0},
+                Wave {asteroids:6,speed:90.0..280.0},
+            ],
+            wave_delay:2.0,
+            saucer_interval:20.0,
+            small_saucer_chance:0.3,
+            scores:ScoreTable::default(),
+            extra_life_every:10000,
+            hyperspace_cooldown:2.0,
+            hyperspace_failure_chance:0.1,
+            shield_drain:0.5,
+            shield_regen:0.1,
+            bullet_lifetime:1.0,
+            bullet_range:750.0,
+            max_player_bullets:4,
+            powerup_chance:0.1,
+            powerup_duration:10.0,
+            flight:FlightModel::default(),
+        }
+    }
+}
+
+
+/// Overwrites `base` with `overrides`, recursing into tables both of them have
+fn merge_values(base:&mut Value,overrides:Value) {
+    match (base,overrides) {
+        (Value::Table(base),Value::Table(overrides))=>{
+            for (key,value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing)=>merge_values(existing,value),
+                    None=>{base.insert(key,value);},
+                }
+            }
+        },
+        (base,overrides)=>*base=overrides,
+    }
+}
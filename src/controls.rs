@@ -0,0 +1,134 @@
+//! The controls screen: rebinds the actions in a `KeyMap` from the keyboard.
+
+
+use minifb::{
+    Key,
+    KeyRepeat,
+    Window,
+};
+use embedded_graphics::{
+    geometry::{
+        Point,
+        Size,
+    },
+    primitives::{
+        PrimitiveStyleBuilder,
+        Rectangle,
+    },
+    text::{
+        Text,
+        TextStyle as EgTextStyle,
+        Alignment,
+    },
+    pixelcolor::BinaryColor,
+    prelude::*,
+};
+use bitmap_font::{
+    tamzen::FONT_10x20,
+    TextStyle,
+};
+use crate::{
+    settings::{
+        Action,
+        KeyMap,
+        RESERVED_KEY,
+        key_name,
+    },
+    Buffer,
+};
+
+
+/// Pixels between the rows of the action list
+const ROW_HEIGHT:i32=30;
+/// Top of the action list
+const LIST_TOP:i32=300;
+
+
+/// State of the controls screen while it's open
+pub struct ControlsScreen {
+    /// Index into `Action::ALL`
+    selected:usize,
+    /// Whether the next key pressed gets bound to the selected action
+    waiting:bool,
+    /// Shown under the list, like why a key couldn't be bound
+    message:String,
+}
+impl ControlsScreen {
+    pub fn new()->ControlsScreen {
+        ControlsScreen {
+            selected:0,
+            waiting:false,
+            message:String::new(),
+        }
+    }
+    /// Handles this frame's key presses. Returns true once the screen should close, which is only
+    /// allowed when `keys` is valid.
+    pub fn update(&mut self,window:&Window,keys:&mut KeyMap)->bool {
+        let action=Action::ALL[self.selected];
+        for key in window.get_keys_pressed(KeyRepeat::No) {
+            if self.waiting {
+                self.waiting=false;
+                self.message=match (key,keys.action_of(key)) {
+                    (RESERVED_KEY,_)=>"Cancelled".to_string(),
+                    (_,Some(other))=>format!("{} is already bound to {}",key_name(key),other.name()),
+                    (_,None)=>{
+                        keys.keys_mut(action).push(key);
+                        String::new()
+                    },
+                };
+                continue;
+            }
+            match key {
+                Key::Up=>self.selected=(self.selected+Action::ALL.len()-1)%Action::ALL.len(),
+                Key::Down=>self.selected=(self.selected+1)%Action::ALL.len(),
+                Key::Enter=>{
+                    self.waiting=true;
+                    self.message=format!("Press a key for {}, Escape to cancel",action.name());
+                },
+                Key::Delete|Key::Backspace=>{
+                    keys.keys_mut(action).clear();
+                    self.message.clear();
+                },
+                RESERVED_KEY=>match keys.validate() {
+                    Ok(())=>{
+                        *self=ControlsScreen::new();
+                        return true;
+                    },
+                    Err(e)=>self.message=e,
+                },
+                _=>{},
+            }
+        }
+        return false;
+    }
+    pub fn draw(&self,buffer:&mut Buffer,keys:&KeyMap) {
+        let style=PrimitiveStyleBuilder::new()
+            .fill_color(BinaryColor::Off)
+            .build();
+        Rectangle::with_center(Point::new(500,500),Size::new(600,500))
+            .into_styled(style)
+            .draw(buffer)
+            .unwrap();
+        let centered=EgTextStyle::with_alignment(Alignment::Center);
+        Text::with_text_style("Controls",Point::new(500,LIST_TOP-60),TextStyle::new(&FONT_10x20, BinaryColor::On),centered)
+            .draw(buffer).unwrap();
+        for (i,&action) in Action::ALL.iter().enumerate() {
+            let y=LIST_TOP+i as i32*ROW_HEIGHT;
+            let marker=if i==self.selected {">"} else {" "};
+            Text::new(&format!("{} {}",marker,action.name()),Point::new(250,y),TextStyle::new(&FONT_10x20, BinaryColor::On))
+                .draw(buffer).unwrap();
+            let names=keys.keys(action)
+                .iter()
+                .map(|key|key_name(*key))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Text::new(&names,Point::new(420,y),TextStyle::new(&FONT_10x20, BinaryColor::On))
+                .draw(buffer).unwrap();
+        }
+        let help_y=LIST_TOP+Action::ALL.len() as i32*ROW_HEIGHT+20;
+        Text::with_text_style("Up/Down to select, Enter to add a key\nDelete to clear, Escape to save and close",Point::new(500,help_y),TextStyle::new(&FONT_10x20, BinaryColor::On),centered)
+            .draw(buffer).unwrap();
+        Text::with_text_style(&self.message,Point::new(500,help_y+60),TextStyle::new(&FONT_10x20, BinaryColor::On),centered)
+            .draw(buffer).unwrap();
+    }
+}
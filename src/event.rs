@@ -0,0 +1,100 @@
+use crate::{
+    saucer::SaucerKind,
+    powerup::PowerUpKind,
+    Vec2,
+};
+
+
+/// Something that happened during a tick. `Game::tick` returns these in the order they happened
+/// so sound, effects and tooling can react without the simulation knowing about them.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Event {
+    /// The player fired a bullet
+    Shot {
+        pos:Vec2,
+        vel:Vec2,
+    },
+    /// An asteroid was destroyed, by a bullet or by a saucer crashing into it
+    AsteroidDestroyed {
+        radius:f32,
+        pos:Vec2,
+    },
+    /// A destroyed asteroid broke into `pieces` smaller ones
+    AsteroidSplit {
+        radius:f32,
+        pos:Vec2,
+        pieces:usize,
+    },
+    /// A new wave started and its asteroids were spawned
+    WaveStarted {
+        wave:u32,
+    },
+    /// A new large asteroid entered the field
+    AsteroidSpawned {
+        radius:f32,
+        pos:Vec2,
+    },
+    /// A saucer flew onto the screen
+    SaucerSpawned {
+        kind:SaucerKind,
+        pos:Vec2,
+    },
+    /// A saucer fired a bullet
+    SaucerShot {
+        pos:Vec2,
+        vel:Vec2,
+    },
+    /// A saucer was shot or crashed into something
+    SaucerDestroyed {
+        kind:SaucerKind,
+        pos:Vec2,
+    },
+    /// The player hit an asteroid, a saucer or a saucer bullet and lost a life
+    PlayerDied {
+        pos:Vec2,
+        /// Lives left after this one
+        lives:u32,
+    },
+    /// The player came back after losing a life
+    PlayerRespawned {
+        pos:Vec2,
+    },
+    /// The player lost their last life
+    GameOver {
+        score:u64,
+    },
+    /// The player jumped to hyperspace. A failed jump also kills the player.
+    Hyperspace {
+        from:Vec2,
+        to:Vec2,
+        failed:bool,
+    },
+    ShieldRaised,
+    /// The shield ran out of energy or was let go
+    ShieldDropped,
+    /// An asteroid bounced off the shield, or a saucer bullet was stopped by it
+    ShieldBounce {
+        pos:Vec2,
+    },
+    /// A power-up dropped out of a destroyed asteroid
+    PowerUpSpawned {
+        kind:PowerUpKind,
+        pos:Vec2,
+    },
+    PowerUpCollected {
+        kind:PowerUpKind,
+        pos:Vec2,
+    },
+    /// Points were awarded for destroying something at `pos`
+    Scored {
+        points:u64,
+        pos:Vec2,
+    },
+    /// The score passed an extra life threshold
+    ExtraLife {
+        lives:u32,
+    },
+    ScoreChanged {
+        score:u64,
+    },
+}
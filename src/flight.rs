@@ -0,0 +1,71 @@
+//! How the player's ship handles: thrust, drag, top speed and turning.
+
+
+use std::str::FromStr;
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+
+/// Thrust both presets share, in units/s/s
+const THRUST:f32=600.0;
+/// Turning speed both presets share, in deg/s
+const ROTATE_SPEED:f32=210.0;
+
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(default,deny_unknown_fields)]
+pub struct FlightModel {
+    /// Forward acceleration in units/s/s
+    pub thrust:f32,
+    /// Backward acceleration in units/s/s
+    pub reverse_thrust:f32,
+    /// Fraction of its speed the ship loses per second while coasting. 0 keeps it drifting forever.
+    pub drag:f32,
+    /// Speed the ship can't go past, in units/s
+    pub max_speed:f32,
+    /// Fastest turn in deg/s
+    pub rotate_speed:f32,
+    /// How quickly turning speeds up and slows down, in deg/s/s. Infinite (`inf` in a config
+    /// file) turns at full speed straight away.
+    pub rotate_accel:f32,
+}
+impl FlightModel {
+    /// Drag, a speed cap, weaker reverse thrust and some weight to turning. Easy to control.
+    pub fn arcade()->FlightModel {
+        FlightModel {
+            thrust:THRUST,
+            reverse_thrust:THRUST/2.0,
+            drag:0.5,
+            max_speed:500.0,
+            rotate_speed:ROTATE_SPEED,
+            rotate_accel:ROTATE_SPEED*8.0,
+        }
+    }
+    /// No drag or speed cap and instant turning. The ship keeps all the speed it builds up.
+    pub fn newtonian()->FlightModel {
+        FlightModel {
+            thrust:THRUST,
+            reverse_thrust:THRUST,
+            drag:0.0,
+            max_speed:f32::INFINITY,
+            rotate_speed:ROTATE_SPEED,
+            rotate_accel:f32::INFINITY,
+        }
+    }
+}
+impl Default for FlightModel {
+    fn default()->FlightModel {FlightModel::arcade()}
+}
+impl FromStr for FlightModel {
+    type Err=String;
+    /// Looks up a preset by name
+    fn from_str(s:&str)->Result<FlightModel,String> {
+        match s {
+            "arcade"=>Ok(FlightModel::arcade()),
+            "newtonian"=>Ok(FlightModel::newtonian()),
+            _=>Err(format!("Unknown flight model: `{}`",s)),
+        }
+    }
+}
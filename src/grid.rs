@@ -0,0 +1,102 @@
+//! Uniform grid over the wrapping world for the collision broad phase. Things are inserted into
+//! every cell their bounding box touches, so two overlapping circles always share a cell.
+
+
+use crate::Vec2;
+
+
+pub struct Grid {
+    cell_size:Vec2,
+    cols:usize,
+    rows:usize,
+    cells:Vec<Vec<usize>>,
+}
+impl Grid {
+    /// A grid covering `size` with cells close to `cell_size` wide. The cells are stretched so a
+    /// whole number of them fits the world.
+    pub fn new(size:Vec2,cell_size:f32)->Grid {
+        let cols=((size.x/cell_size).floor() as usize).max(1);
+        let rows=((size.y/cell_size).floor() as usize).max(1);
+        Grid {
+            cell_size:Vec2::new(size.x/cols as f32,size.y/rows as f32),
+            cols,
+            rows,
+            cells:vec![Vec::new();cols*rows],
+        }
+    }
+    /// Empties every cell but keeps their allocations
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+    pub fn insert(&mut self,index:usize,pos:Vec2,radius:f32) {
+        let (xs,ys)=self.cell_range(pos,radius);
+        for y in ys.clone() {
+            for x in xs.clone() {
+                let cell=self.cell_index(x,y);
+                self.cells[cell].push(index);
+            }
+        }
+    }
+    /// Collects everything sharing a cell with the circle into `out`, without duplicates
+    pub fn query(&self,pos:Vec2,radius:f32,out:&mut Vec<usize>) {
+        out.clear();
+        let (xs,ys)=self.cell_range(pos,radius);
+        for y in ys {
+            for x in xs.clone() {
+                out.extend_from_slice(&self.cells[self.cell_index(x,y)]);
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+    /// Unwrapped cell coordinates covered by the circle's bounding box. A box wider than the world
+    /// covers every column once instead of wrapping around onto itself.
+    fn cell_range(&self,pos:Vec2,radius:f32)->(std::ops::Range<isize>,std::ops::Range<isize>) {
+        let min=((pos-radius)/self.cell_size).floor();
+        let max=((pos+radius)/self.cell_size).floor();
+        let x0=min.x as isize;
+        let y0=min.y as isize;
+        let x1=(max.x as isize+1).min(x0+self.cols as isize);
+        let y1=(max.y as isize+1).min(y0+self.rows as isize);
+        return (x0..x1,y0..y1);
+    }
+    fn cell_index(&self,x:isize,y:isize)->usize {
+        let x=x.rem_euclid(self.cols as isize) as usize;
+        let y=y.rem_euclid(self.rows as isize) as usize;
+        return y*self.cols+x;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(grid:&Grid,pos:Vec2,radius:f32)->Vec<usize> {
+        let mut out=Vec::new();
+        grid.query(pos,radius,&mut out);
+        return out;
+    }
+
+    #[test]
+    fn circles_share_cells_across_the_edges() {
+        let mut grid=Grid::new(Vec2::new(1000.0,1000.0),100.0);
+        grid.insert(0,Vec2::new(995.0,500.0),10.0);
+        grid.insert(1,Vec2::new(500.0,995.0),10.0);
+        grid.insert(2,Vec2::new(995.0,995.0),10.0);
+        assert_eq!(query(&grid,Vec2::new(5.0,500.0),10.0),vec![0]);
+        assert_eq!(query(&grid,Vec2::new(500.0,5.0),10.0),vec![1]);
+        assert_eq!(query(&grid,Vec2::new(5.0,5.0),10.0),vec![2]);
+    }
+
+    #[test]
+    fn circles_wider_than_the_world_are_found_once() {
+        let mut grid=Grid::new(Vec2::new(1000.0,1000.0),100.0);
+        grid.insert(0,Vec2::new(500.0,500.0),2000.0);
+        assert_eq!(query(&grid,Vec2::new(50.0,950.0),1.0),vec![0]);
+        grid.clear();
+        assert!(query(&grid,Vec2::new(500.0,500.0),2000.0).is_empty());
+    }
+}
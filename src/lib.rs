@@ -0,0 +1,1133 @@
+//! The asteroids simulation: game state, the fixed-step `tick` and rendering into a pixel buffer.
+//! The window, keyboard and the rest of the frontend live in the binary.
+//!
+//! Asteroid vectors created by me. Link: https://vectr.com/design/editor/4af10ec0-ce81-4622-8e33-72842af687f0
+
+#![allow(clippy::needless_return)]
+
+
+use euc::{
+    buffer::Buffer2d,
+    rasterizer::Lines,
+    Pipeline,
+    Target,
+};
+use rand::{
+    Rng,
+    SeedableRng,
+};
+use rand_chacha::ChaCha8Rng;
+use std::ops::Range;
+pub use event::Event;
+pub use config::Config;
+use grid::Grid;
+use saucer::*;
+use powerup::*;
+use score::SizeClass;
+
+
+pub mod svg_to_vector;
+pub mod asset;
+pub mod headless;
+pub mod event;
+pub mod collision;
+pub mod physics;
+pub mod config;
+pub mod grid;
+pub mod wave;
+pub mod saucer;
+pub mod score;
+pub mod powerup;
+pub mod flight;
+
+
+pub type Vec2=vek::Vec2<f32>;
+/// Generator behind every random choice in a game. Its algorithm is fixed, unlike `StdRng`'s, so a
+/// seed replays the same on every build.
+pub type GameRng=ChaCha8Rng;
+
+
+pub const TICK_RATE:usize=120;
+pub const TICK:f32=1.0/(TICK_RATE as f32);
+pub const PLAYER_RADIUS:f32=10.0;
+/// Fastest a new asteroid spins, in rad/s
+pub const ASTEROID_MAX_SPIN:f32=1.5;
+/// Most a split piece's spin differs from its parent's, in rad/s
+pub const SPLIT_SPIN_CHANGE:f32=1.0;
+/// Random positions tried for a new asteroid before settling for the one furthest from the player
+pub const SPAWN_ATTEMPTS:usize=32;
+/// Extra space kept between the player and a newly placed asteroid or a respawn
+pub const SAFE_SPAWN_MARGIN:f32=50.0;
+/// How many times per second an invulnerable player blinks
+pub const INVULNERABLE_BLINK_RATE:f32=8.0;
+/// Radius of the shield bubble around the player
+pub const SHIELD_RADIUS:f32=25.0;
+/// Number of lines the shield bubble is drawn with
+pub const SHIELD_SEGMENTS:usize=24;
+/// Distance between the lives icons in pixels
+pub const LIFE_ICON_SPACING:f32=25.0;
+pub const PLAYER_VECTOR:&[Vec2]=&[
+    Vec2::new(0.0,-0.02),
+    Vec2::new(0.01,0.01),
+    Vec2::new(0.0,-0.02),
+    Vec2::new(-0.01,0.01),
+    Vec2::new(-0.01,0.01),
+    Vec2::new(-0.02,0.02),
+    Vec2::new(0.01,0.01),
+    Vec2::new(0.02,0.02),
+    Vec2::new(0.01,0.01),
+    Vec2::new(-0.01,0.01),
+    Vec2::new(0.01,0.01),
+    Vec2::new(0.0,0.02),
+    Vec2::new(-0.01,0.01),
+    Vec2::new(0.0,0.02),
+];
+pub const BULLET_VECTOR:&[Vec2]=&[
+    Vec2::new(0.0,-0.005),
+    Vec2::new(0.005,0.005),
+    Vec2::new(0.0,-0.005),
+    Vec2::new(-0.005,0.005),
+];
+
+
+/// True if an asteroid at `pos` is far enough from a player at `player` to not hit them right
+/// away
+fn is_safe_spawn(pos:Vec2,radius:f32,player:Vec2,size:Vec2)->bool {
+    collision::wrapped_offset(pos,player,size).magnitude()>=radius+PLAYER_RADIUS+SAFE_SPAWN_MARGIN
+}
+/// Wraps a position that just left the world of `size` back onto the other side
+fn wrap_position(mut pos:Vec2,size:Vec2)->Vec2 {
+    if pos.x>=size.x {
+        pos.x=0.0;
+    } else if pos.x<0.0 {
+        pos.x=size.x;
+    }
+    if pos.y>=size.y {
+        pos.y=0.0;
+    } else if pos.y<0.0 {
+        pos.y=size.y;
+    }
+    return pos;
+}
+/// Angle for `Bullet::dir` that points along `vel`
+fn bullet_dir(vel:Vec2)->f32 {
+    vel.x.atan2(-vel.y)
+}
+/// Adds `lines` centered on `pos` in screen space, plus a copy past every screen edge they hang
+/// over, so things slide across the edges instead of popping from one side to the other
+fn push_wrapped(vertices:&mut Vec<Vec2>,lines:&[Vec2],pos:Vec2) {
+    let extent=collision::extent(lines);
+    let mut xs=vec![0.0];
+    let mut ys=vec![0.0];
+    if pos.x-extent< -1.0 {xs.push(2.0)}
+    if pos.x+extent>1.0 {xs.push(-2.0)}
+    if pos.y-extent< -1.0 {ys.push(2.0)}
+    if pos.y+extent>1.0 {ys.push(-2.0)}
+    for &x in xs.iter() {
+        for &y in ys.iter() {
+            let offset=pos+Vec2::new(x,y);
+            vertices.extend(lines.iter().map(|vtx|vtx+offset));
+        }
+    }
+}
+
+
+#[derive(Debug,Clone,Copy,Default)]
+pub struct Buttons {
+    pub forward:bool,
+    pub backward:bool,
+    pub left:bool,
+    pub right:bool,
+    pub fire:bool,
+    pub hyperspace:bool,
+    pub shield:bool,
+}
+pub struct Asteroid {
+    pub pos:Vec2,
+    /// Position at the start of the last tick, for interpolated rendering
+    pub prev_pos:Vec2,
+    pub vel:Vec2,
+    pub radius:f32,
+    pub model_index:usize,
+    /// Rotation of the outline in radians
+    pub angle:f32,
+    pub prev_angle:f32,
+    /// Spin in rad/s
+    pub angular_vel:f32,
+}
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum BulletOwner {
+    Player,
+    Saucer,
+}
+pub struct Bullet {
+    pub pos:Vec2,
+    pub prev_pos:Vec2,
+    pub vel:Vec2,
+    pub dir:f32,
+    pub owner:BulletOwner,
+    /// Game time the bullet disappears at
+    pub expires_at:f32,
+    /// Distance the bullet can still travel before it disappears
+    pub range_left:f32,
+    /// Keeps going after hitting an asteroid
+    pub piercing:bool,
+}
+pub struct Player {
+    pub pos:Vec2,
+    pub prev_pos:Vec2,
+    pub vel:Vec2,
+    pub dir:f32,
+    pub prev_dir:f32,
+    /// Turning speed in rad/s
+    pub angular_vel:f32,
+    /// Game time of the last shot
+    pub last_shoot:f32,
+    /// Game time until which asteroids pass through the player
+    pub invulnerable_until:f32,
+    /// Game time of the last jump to hyperspace
+    pub last_hyperspace:f32,
+    /// Shield energy from 0 to 1
+    pub shield_energy:f32,
+    /// Whether the shield is up this tick
+    pub shielded:bool,
+    /// Timed power-ups in effect and the game time each runs out at
+    pub powerups:Vec<(PowerUpKind,f32)>,
+}
+impl Asteroid {
+    /// A new asteroid with a radius from `radius` at a random position in a world of `size` that
+    /// is a safe distance from `avoid`. Each velocity component is picked from `speed`.
+    pub fn random(rng:&mut GameRng,model_range:Range<usize>,size:Vec2,avoid:Vec2,radius:Range<f32>,speed:Range<f32>)->Asteroid {
+        let radius=rng.gen_range(radius);
+        let mut pos=Vec2::new(rng.gen_range(0.0..size.x),rng.gen_range(0.0..size.y));
+        for _ in 1..SPAWN_ATTEMPTS {
+            if is_safe_spawn(pos,radius,avoid,size) {
+                break;
+            }
+            let candidate=Vec2::new(rng.gen_range(0.0..size.x),rng.gen_range(0.0..size.y));
+            let distance=|pos|collision::wrapped_offset(pos,avoid,size).magnitude_squared();
+            if distance(candidate)>distance(pos) {
+                pos=candidate;
+            }
+        }
+        let angle=rng.gen_range(0.0..std::f32::consts::TAU);
+        Asteroid {
+            pos,
+            prev_pos:pos,
+            vel:Vec2::new(rng.gen_range(speed.clone()),rng.gen_range(speed)),
+            radius,
+            model_index:rng.gen_range(model_range),
+            angle,
+            prev_angle:angle,
+            angular_vel:rng.gen_range(-ASTEROID_MAX_SPIN..ASTEROID_MAX_SPIN),
+        }
+    }
+    /// Mass for collision response. Proportional to area.
+    pub fn mass(&self)->f32 {
+        self.radius*self.radius
+    }
+    /// Moment of inertia of a disc with the asteroid's mass and radius
+    pub fn inertia(&self)->f32 {
+        0.5*self.mass()*self.radius*self.radius
+    }
+}
+impl Player {
+    pub fn new(pos:Vec2)->Player {
+        Player {
+            pos,
+            prev_pos:pos,
+            vel:Vec2::zero(),
+            dir:0.0,
+            prev_dir:0.0,
+            angular_vel:0.0,
+            last_shoot:0.0,
+            invulnerable_until:0.0,
+            last_hyperspace:f32::NEG_INFINITY,
+            shield_energy:1.0,
+            shielded:false,
+            powerups:Vec::new(),
+        }
+    }
+    pub fn has_powerup(&self,kind:PowerUpKind)->bool {
+        self.powerups.iter().any(|(k,_)|*k==kind)
+    }
+}
+pub struct Game {
+    asteroids:Vec<Asteroid>,
+    bullets:Vec<Bullet>,
+    player:Player,
+    saucer:Option<Saucer>,
+    powerups:Vec<PowerUp>,
+    /// Game time the next saucer shows up at
+    next_saucer_at:f32,
+    lives:u32,
+    /// The player lost a life and is waiting for the center to clear before coming back
+    respawning:bool,
+    game_over:bool,
+    score:u64,
+    /// Score the next extra life is awarded at
+    next_extra_life:u64,
+    wave:u32,
+    /// Game time the current wave started at
+    wave_start:f32,
+    /// Game time to start the next wave at, once the field is clear
+    next_wave_at:Option<f32>,
+    size:Vec2,
+    /// Asteroid outlines, centered and scaled to a radius of 1
+    asteroid_vectors:Vec<Vec<Vec2>>,
+    asteroid_vector_range:Range<usize>,
+    /// Seed of the current game. Every random choice comes from `rng`, so the same seed and
+    /// inputs replay the same game.
+    seed:u64,
+    rng:GameRng,
+    /// Simulated seconds since the game started
+    time:f32,
+    config:Config,
+    /// Scratch space for collision queries, rebuilt every tick
+    grid:Grid,
+}
+impl Pipeline for Game {
+    type Vertex=Vec2;
+    type VsOut=();
+    type Pixel=u32;
+    fn vert(&self,pos:&Self::Vertex)->([f32;4],Self::VsOut) {
+        return ([pos.x,pos.y,0.0,1.0],());
+    }
+    fn frag(&self,_:&Self::VsOut)->Self::Pixel {
+        u32::from_le_bytes([200,200,200,255])   // light grey
+    }
+}
+impl Game {
+    /// Creates a new game from `seed` with the default config. Panics if the world is too small for
+    /// it, see `with_shapes`.
+    pub fn new(size:[f32;2],seed:u64)->Game {
+        Game::with_config(size,seed,Config::default()).unwrap()
+    }
+    /// Creates a new game from `seed` with the built-in asteroid shapes. Use
+    /// `asset::load_asteroid_shapes` and `with_shapes` for shapes from disk.
+    pub fn with_config(size:[f32;2],seed:u64,config:Config)->Result<Game,String> {
+        let shapes=asset::load_asteroid_shapes(&asset::AssetPath::default(),config.curve_tolerance,&mut Vec::new());
+        Game::with_shapes(size,seed,config,shapes)
+    }
+    /// Creates a new game from `seed` with the given asteroid shapes, each a line list with a radius
+    /// of 1. The built-in shapes are used if there are none. Fails if `config` isn't valid or the
+    /// largest asteroid can't be kept `SAFE_SPAWN_MARGIN` away from the player in a world of `size`.
+    pub fn with_shapes(size:[f32;2],seed:u64,config:Config,mut asteroid_vectors:Vec<Vec<Vec2>>)->Result<Game,String> {
+        config.validate()?;
+        let half_size=size[0].min(size[1])/2.0;
+        if config.asteroid_max_radius+PLAYER_RADIUS+SAFE_SPAWN_MARGIN>half_size {
+            return Err(format!(
+                "`asteroid_max_radius` can be at most {} in a {}x{} world",
+                half_size-PLAYER_RADIUS-SAFE_SPAWN_MARGIN,
+                size[0],
+                size[1],
+            ));
+        }
+        if asteroid_vectors.is_empty() {
+            asteroid_vectors=asset::builtin_shapes();
+        }
+        let rng=GameRng::seed_from_u64(seed);
+        let size=Vec2::new(size[0],size[1]);
+        let mut game=Game {
+            asteroid_vector_range:0..asteroid_vectors.len(),
+            asteroids:Vec::new(),
+            bullets:Vec::new(),
+            player:Player::new(size/2.0),
+            saucer:None,
+            next_saucer_at:0.0,
+            powerups:Vec::new(),
+            lives:config.lives,
+            respawning:false,
+            game_over:false,
+            score:0,
+            next_extra_life:config.extra_life_every,
+            wave:0,
+            wave_start:0.0,
+            next_wave_at:None,
+            size,
+            asteroid_vectors,
+            seed,
+            rng,
+            time:0.0,
+            // About the size of the largest asteroid
+            grid:Grid::new(size,config.asteroid_max_radius*2.0),
+            config,
+        };
+        game.schedule_saucer();
+        game.start_wave(1);
+        Ok(game)
+    }
+    /// Starts a new game with a seed drawn from the current one, so a whole session can be
+    /// replayed from the first seed.
+    pub fn reset(&mut self) {
+        self.seed=self.rng.gen();
+        self.rng=GameRng::seed_from_u64(self.seed);
+        self.player=Player::new(self.size/2.0);
+        self.asteroids=Vec::new();
+        self.bullets=Vec::new();
+        self.powerups=Vec::new();
+        self.lives=self.config.lives;
+        self.respawning=false;
+        self.game_over=false;
+        self.score=0;
+        self.next_extra_life=self.config.extra_life_every;
+        self.time=0.0;
+        self.next_wave_at=None;
+        self.saucer=None;
+        self.schedule_saucer();
+        self.start_wave(1);
+    }
+    /// Spawns the asteroids for wave `number` away from the player
+    fn start_wave(&mut self,number:u32)->Vec<Event> {
+        let settings=wave::wave_settings(&self.config.waves,number);
+        let mut events=vec![Event::WaveStarted{wave:number}];
+        self.wave=number;
+        self.wave_start=self.time;
+        for _ in 0..settings.asteroids {
+            let asteroid=Asteroid::random(
+                &mut self.rng,
+                self.asteroid_vector_range.clone(),
+                self.size,
+                self.player.pos,
+                self.config.new_asteroid_min_radius..self.config.asteroid_max_radius,
+                settings.speed.clone(),
+            );
+            events.push(Event::AsteroidSpawned{radius:asteroid.radius,pos:asteroid.pos});
+            self.asteroids.push(asteroid);
+        }
+        return events;
+    }
+    pub fn is_game_over(&self)->bool {self.game_over}
+    pub fn seed(&self)->u64 {self.seed}
+    pub fn score(&self)->u64 {self.score}
+    pub fn lives(&self)->u32 {self.lives}
+    pub fn wave(&self)->u32 {self.wave}
+    /// Seconds since the current wave started
+    pub fn wave_time(&self)->f32 {self.time-self.wave_start}
+    pub fn is_respawning(&self)->bool {self.respawning}
+    pub fn is_invulnerable(&self)->bool {self.time<self.player.invulnerable_until}
+    /// Simulated seconds since the game started
+    pub fn time(&self)->f32 {self.time}
+    pub fn size(&self)->Vec2 {self.size}
+    pub fn asteroids(&self)->&[Asteroid] {&self.asteroids}
+    pub fn bullets(&self)->&[Bullet] {&self.bullets}
+    pub fn player(&self)->&Player {&self.player}
+    pub fn saucer(&self)->Option<&Saucer> {self.saucer.as_ref()}
+    /// Power-ups waiting to be picked up
+    pub fn powerups(&self)->&[PowerUp] {&self.powerups}
+    /// Timed power-ups in effect and the seconds left on each
+    pub fn powerup_timers(&self)->impl Iterator<Item=(PowerUpKind,f32)>+'_ {
+        self.player.powerups.iter().map(move|&(kind,until)|(kind,until-self.time))
+    }
+    /// Shield energy from 0 to 1
+    pub fn shield_energy(&self)->f32 {self.player.shield_energy}
+    /// How far the hyperspace cooldown has recharged, from 0 to 1
+    pub fn hyperspace_charge(&self)->f32 {
+        if self.config.hyperspace_cooldown<=0.0 {return 1.0}
+        ((self.time-self.player.last_hyperspace)/self.config.hyperspace_cooldown).clamp(0.0,1.0)
+    }
+    pub fn config(&self)->&Config {&self.config}
+    /// Swaps in new asteroid shapes, like `with_shapes` does. Asteroids already in play keep their
+    /// shape if it's still there and take another one if it isn't.
+    pub fn set_shapes(&mut self,mut shapes:Vec<Vec<Vec2>>) {
+        if shapes.is_empty() {
+            shapes=asset::builtin_shapes();
+        }
+        for asteroid in self.asteroids.iter_mut() {
+            asteroid.model_index%=shapes.len();
+        }
+        self.asteroid_vector_range=0..shapes.len();
+        self.asteroid_vectors=shapes;
+    }
+    /// Advances the simulation by one fixed step of `TICK` seconds and returns what happened
+    /// during it. Check `is_game_over` to see if the player got hit.
+    pub fn tick(&mut self,buttons:Buttons)->Vec<Event> {
+        let mut events=Vec::new();
+        // short-circuit and process nothing if we are in the game over state
+        if self.game_over {return events}
+        let delta=TICK;
+        self.time+=delta;
+        // The player sits still and can't act while waiting to respawn
+        let buttons=if self.respawning {Buttons::default()} else {buttons};
+        for a in self.asteroids.iter_mut() {
+            a.prev_pos=a.pos;
+            a.prev_angle=a.angle;
+            a.angle+=a.angular_vel*delta;
+            a.pos=wrap_position(a.pos+a.vel*delta,self.size);
+        }
+        for b in self.bullets.iter_mut() {
+            b.prev_pos=b.pos;
+            b.pos=wrap_position(b.pos+b.vel*delta,self.size);
+            b.range_left-=b.vel.magnitude()*delta;
+        }
+        self.player.prev_pos=self.player.pos;
+        self.player.prev_dir=self.player.dir;
+        self.player.pos+=self.player.vel*delta;
+        self.fly(buttons);
+        self.player.pos=wrap_position(self.player.pos,self.size);
+        self.update_powerups(&mut events);
+        let player_bullets=self.bullets.iter().filter(|b|b.owner==BulletOwner::Player).count();
+        let shot_delay=if self.player.has_powerup(PowerUpKind::RapidFire) {
+            self.config.shot_delay.min(RAPID_FIRE_DELAY)
+        } else {
+            self.config.shot_delay
+        };
+        if buttons.fire&&self.time-self.player.last_shoot>=shot_delay&&player_bullets<self.config.max_player_bullets {
+            self.player.last_shoot=self.time;
+            // Middle first, so a spread shot near the bullet cap still goes straight
+            let angles:&[f32]=if self.player.has_powerup(PowerUpKind::Spread) {
+                &[0.0,-SPREAD_ANGLE,SPREAD_ANGLE]
+            } else {
+                &[0.0]
+            };
+            for angle in angles.iter().take(self.config.max_player_bullets-player_bullets) {
+                let dir=self.player.dir+angle.to_radians();
+                let bullet=Bullet {
+                    pos:self.player.pos,
+                    prev_pos:self.player.pos,
+                    vel:self.player.vel-Vec2::new(0.0,self.config.bullet_speed).rotated_z(dir),
+                    dir,
+                    owner:BulletOwner::Player,
+                    expires_at:self.time+self.config.bullet_lifetime,
+                    range_left:self.config.bullet_range,
+                    piercing:self.player.has_powerup(PowerUpKind::Piercing),
+                };
+                events.push(Event::Shot{pos:bullet.pos,vel:bullet.vel});
+                self.bullets.push(bullet);
+            }
+        }
+        if buttons.hyperspace&&self.time-self.player.last_hyperspace>=self.config.hyperspace_cooldown {
+            self.hyperspace(&mut events);
+        }
+        self.update_shield(buttons.shield,&mut events);
+        self.update_saucer(&mut events);
+        self.bounce_off_shield(&mut events);
+        // Everything is drawn in screen space (-1..1), so this takes the shapes to world space
+        let screen_scale=self.size/2.0;
+        let player_extent=collision::extent(PLAYER_VECTOR)*screen_scale.reduce_partial_max();
+        // Broad phase for bullets, each covering the whole path it took this tick
+        self.grid.clear();
+        for (i,bullet) in self.bullets.iter().enumerate() {
+            let step=collision::wrapped_offset(bullet.prev_pos,bullet.pos,self.size);
+            self.grid.insert(i,bullet.pos-step/2.0,step.magnitude()/2.0);
+        }
+        let mut spent=vec![false;self.bullets.len()];
+        let mut nearby=Vec::new();
+        let mut new_asteroids=Vec::new();
+        let mut asteroid_count=self.asteroids.len();
+        let can_hit_player=self.can_hit_player();
+        let mut player_hit=false;
+        let mut saucer_crashed=false;
+        let mut awards=Vec::new();
+        let mut new_powerups=Vec::new();
+        self.asteroids.retain(|asteroid|{
+            // Only build the asteroid's outline once something is inside its bounding circle
+            // Asteroid vectors have a radius of 1
+            let extent=asteroid.radius;
+            let outline=||collision::transform(&self.asteroid_vectors[asteroid.model_index],asteroid.pos,Vec2::broadcast(extent),asteroid.angle);
+            let mut lines=None;
+            // Collide asteroid-bullet then delete the asteroid and bullet if they collide. Bullets
+            // are tested along the whole path they took this tick so they can't skip over small
+            // asteroids. Everything is moved next to the asteroid first, so hits work across the
+            // screen edges.
+            let mut hit=false;
+            let mut scored=false;
+            self.grid.query(asteroid.pos,extent,&mut nearby);
+            for &i in nearby.iter() {
+                if spent[i] {continue}
+                let bullet=&self.bullets[i];
+                let step=collision::wrapped_offset(bullet.prev_pos,bullet.pos,self.size);
+                let offset=collision::wrapped_offset(asteroid.pos,bullet.pos,self.size);
+                if offset.magnitude_squared()<=(extent+step.magnitude()).powi(2) {
+                    let lines=lines.get_or_insert_with(outline);
+                    let pos=asteroid.pos+offset;
+                    if collision::segment_hits_lines(pos-step,pos,lines) {
+                        hit=true;
+                        scored=bullet.owner==BulletOwner::Player;
+                        if !bullet.piercing {
+                            spent[i]=true;
+                        }
+                        break;
+                    }
+                }
+            }
+            // Collide asteroid-player then take a life, or set game over on the last one
+            if can_hit_player&&!player_hit {
+                let offset=collision::wrapped_offset(asteroid.pos,self.player.pos,self.size);
+                if offset.magnitude_squared()<=(extent+player_extent).powi(2)&&collision::lines_overlap(
+                    &collision::transform(PLAYER_VECTOR,asteroid.pos+offset,screen_scale,self.player.dir),
+                    lines.get_or_insert_with(outline),
+                ) {
+                    player_hit=true;
+                }
+            }
+            // Collide asteroid-saucer then destroy both
+            if let Some(saucer)=self.saucer.as_ref().filter(|_|!saucer_crashed) {
+                let scale=screen_scale*saucer.kind.scale();
+                let saucer_extent=collision::extent(SAUCER_VECTOR)*scale.reduce_partial_max();
+                let offset=collision::wrapped_offset(asteroid.pos,saucer.pos,self.size);
+                if offset.magnitude_squared()<=(extent+saucer_extent).powi(2)&&collision::lines_overlap(
+                    &collision::transform(SAUCER_VECTOR,asteroid.pos+offset,scale,0.0),
+                    lines.get_or_insert_with(outline),
+                ) {
+                    saucer_crashed=true;
+                    hit=true;
+                }
+            }
+            if hit {
+                events.push(Event::AsteroidDestroyed{radius:asteroid.radius,pos:asteroid.pos});
+                asteroid_count-=1;
+                if scored {
+                    awards.push((self.config.scores.asteroid(SizeClass::of(asteroid.radius,&self.config)),asteroid.pos));
+                    if self.rng.gen::<f32>()<self.config.powerup_chance {
+                        new_powerups.push(PowerUp {
+                            kind:PowerUpKind::ALL[self.rng.gen_range(0..PowerUpKind::ALL.len())],
+                            pos:asteroid.pos,
+                            prev_pos:asteroid.pos,
+                            vel:asteroid.vel/4.0,
+                            expires_at:self.time+POWERUP_LIFETIME,
+                        });
+                    }
+                }
+                if asteroid_count<self.config.max_asteroids&&asteroid.radius>self.config.asteroid_min_radius {
+                    let amt=self.rng.gen_range(2..=4);
+                    let radius=asteroid.radius/(amt as f32);
+                    asteroid_count+=amt;
+                    events.push(Event::AsteroidSplit{radius:asteroid.radius,pos:asteroid.pos,pieces:amt});
+                    for _ in 0..amt {
+                        let pos=asteroid.pos+Vec2::new(self.rng.gen_range(0.0..radius),self.rng.gen_range(0.0..radius));
+                        new_asteroids.push(Asteroid {
+                            pos,
+                            prev_pos:pos,
+                            vel:asteroid.vel+Vec2::new(self.rng.gen_range(10.0..75.0),self.rng.gen_range(10.0..75.0)),
+                            model_index:self.rng.gen_range(self.asteroid_vector_range.clone()),
+                            radius,
+                            angle:asteroid.angle,
+                            prev_angle:asteroid.angle,
+                            angular_vel:asteroid.angular_vel+self.rng.gen_range(-SPLIT_SPIN_CHANGE..SPLIT_SPIN_CHANGE),
+                        });
+                    }
+                }
+            }
+            !hit
+        });
+        for (points,pos) in awards {
+            self.award(points,pos,&mut events);
+        }
+        for powerup in new_powerups {
+            events.push(Event::PowerUpSpawned{kind:powerup.kind,pos:powerup.pos});
+            self.powerups.push(powerup);
+        }
+        if saucer_crashed {
+            self.destroy_saucer(&mut events);
+        }
+        self.collide_saucer(&mut spent,&mut player_hit,&mut events);
+        if player_hit {
+            self.kill_player(&mut events);
+        }
+        // Remove the bullets that hit something or ran out of time or range
+        let mut spent=spent.into_iter();
+        let time=self.time;
+        self.bullets.retain(|b|!spent.next().unwrap_or(false)&&time<b.expires_at&&b.range_left>0.0);
+        self.asteroids.append(&mut new_asteroids);
+        physics::resolve_asteroid_collisions(&mut self.asteroids,self.size,self.config.asteroid_restitution,self.config.asteroid_friction,&mut self.grid);
+        // Start the next wave a little while after the field is cleared
+        if self.asteroids.is_empty() {
+            match self.next_wave_at {
+                None=>self.next_wave_at=Some(self.time+self.config.wave_delay),
+                Some(at) if self.time>=at=>{
+                    self.next_wave_at=None;
+                    events.extend(self.start_wave(self.wave+1));
+                },
+                Some(_)=>{},
+            }
+        }
+        // Bring the player back in the center once no asteroid is close enough to hit them
+        if self.respawning {
+            let center=self.size/2.0;
+            if self.asteroids.iter().all(|a|is_safe_spawn(a.pos,a.radius,center,self.size)) {
+                self.player=Player::new(center);
+                self.player.invulnerable_until=self.time+self.config.respawn_invulnerability;
+                self.respawning=false;
+                events.push(Event::PlayerRespawned{pos:center});
+            }
+        }
+        events
+    }
+    /// Turns and thrusts the player according to the flight model
+    fn fly(&mut self,buttons:Buttons) {
+        let delta=TICK;
+        let flight=&self.config.flight;
+        let player=&mut self.player;
+        let turn=buttons.left as i32-buttons.right as i32;
+        let target=turn as f32*flight.rotate_speed.to_radians();
+        let step=flight.rotate_accel.to_radians()*delta;
+        player.angular_vel+=(target-player.angular_vel).clamp(-step,step);
+        player.dir+=player.angular_vel*delta;
+        if buttons.forward {
+            player.vel-=Vec2::new(0.0,flight.thrust*delta).rotated_z(player.dir);
+        }
+        if buttons.backward {
+            player.vel-=Vec2::new(0.0,-flight.reverse_thrust*delta).rotated_z(player.dir);
+        }
+        if !buttons.forward&&!buttons.backward {
+            player.vel*=(1.0-flight.drag*delta).max(0.0);
+        }
+        let speed=player.vel.magnitude();
+        if speed>flight.max_speed {
+            player.vel*=flight.max_speed/speed;
+        }
+    }
+    /// False while the player is dead, respawning, invulnerable or shielded
+    fn can_hit_player(&self)->bool {
+        !self.game_over&&!self.respawning&&self.time>=self.player.invulnerable_until&&!self.player.shielded&&!self.config.disable_game_over
+    }
+    /// Teleports the player somewhere random. It blows up on arrival if the jump fails.
+    fn hyperspace(&mut self,events:&mut Vec<Event>) {
+        let from=self.player.pos;
+        let to=Vec2::new(self.rng.gen_range(0.0..self.size.x),self.rng.gen_range(0.0..self.size.y));
+        let failed=self.rng.gen::<f32>()<self.config.hyperspace_failure_chance;
+        self.player.last_hyperspace=self.time;
+        self.player.pos=to;
+        self.player.prev_pos=to;
+        events.push(Event::Hyperspace{from,to,failed});
+        if failed&&!self.config.disable_game_over {
+            self.kill_player(events);
+        }
+    }
+    /// Drains shield energy while `held`, and recharges it while not. The shield power-up holds it
+    /// up for free.
+    fn update_shield(&mut self,held:bool,events:&mut Vec<Event>) {
+        let delta=TICK;
+        let player=&mut self.player;
+        let powered=player.has_powerup(PowerUpKind::Shield);
+        let shielded=powered||held&&player.shield_energy>0.0;
+        if shielded&&!powered {
+            player.shield_energy=(player.shield_energy-self.config.shield_drain*delta).max(0.0);
+        } else if !shielded {
+            player.shield_energy=(player.shield_energy+self.config.shield_regen*delta).min(1.0);
+        }
+        if shielded!=player.shielded {
+            player.shielded=shielded;
+            events.push(if shielded {Event::ShieldRaised} else {Event::ShieldDropped});
+        }
+    }
+    /// Moves power-ups, drops the ones that ran out and collects the ones the player touches
+    fn update_powerups(&mut self,events:&mut Vec<Event>) {
+        let delta=TICK;
+        let time=self.time;
+        self.player.powerups.retain(|&(_,until)|time<until);
+        for powerup in self.powerups.iter_mut() {
+            powerup.prev_pos=powerup.pos;
+            powerup.pos=wrap_position(powerup.pos+powerup.vel*delta,self.size);
+        }
+        self.powerups.retain(|p|time<p.expires_at);
+        if self.respawning||self.game_over {return}
+        let mut i=0;
+        while i<self.powerups.len() {
+            let offset=collision::wrapped_offset(self.player.pos,self.powerups[i].pos,self.size);
+            if offset.magnitude()>POWERUP_RADIUS+PLAYER_RADIUS {
+                i+=1;
+                continue;
+            }
+            let powerup=self.powerups.remove(i);
+            events.push(Event::PowerUpCollected{kind:powerup.kind,pos:powerup.pos});
+            if powerup.kind.is_timed() {
+                // Picking up one that's already running starts its timer over
+                let until=time+self.config.powerup_duration;
+                match self.player.powerups.iter_mut().find(|(k,_)|*k==powerup.kind) {
+                    Some(active)=>active.1=until,
+                    None=>self.player.powerups.push((powerup.kind,until)),
+                }
+            } else {
+                self.lives+=1;
+                events.push(Event::ExtraLife{lives:self.lives});
+            }
+        }
+    }
+    /// Bounces asteroids that touch the shield away from the player
+    fn bounce_off_shield(&mut self,events:&mut Vec<Event>) {
+        if !self.player.shielded {return}
+        let player=&self.player;
+        for asteroid in self.asteroids.iter_mut() {
+            let offset=collision::wrapped_offset(player.pos,asteroid.pos,self.size);
+            let dist=offset.magnitude();
+            let min_dist=SHIELD_RADIUS+asteroid.radius;
+            if dist>=min_dist||dist==0.0 {continue}
+            // The player is treated as immovable, so only the asteroid changes course
+            let normal=offset/dist;
+            let closing=(asteroid.vel-player.vel).dot(normal);
+            if closing<0.0 {
+                asteroid.vel-=normal*closing*(1.0+self.config.asteroid_restitution);
+                events.push(Event::ShieldBounce{pos:player.pos+normal*SHIELD_RADIUS});
+            }
+            asteroid.pos=wrap_position(player.pos+normal*min_dist,self.size);
+        }
+    }
+    /// Adds `points` for something destroyed at `pos`, and an extra life for every threshold the
+    /// score passes
+    fn award(&mut self,points:u64,pos:Vec2,events:&mut Vec<Event>) {
+        self.score+=points;
+        events.push(Event::Scored{points,pos});
+        events.push(Event::ScoreChanged{score:self.score});
+        while self.config.extra_life_every>0&&self.score>=self.next_extra_life {
+            self.next_extra_life+=self.config.extra_life_every;
+            self.lives+=1;
+            events.push(Event::ExtraLife{lives:self.lives});
+        }
+    }
+    /// Takes a life, or sets game over on the last one
+    fn kill_player(&mut self,events:&mut Vec<Event>) {
+        self.player.powerups.clear();
+        self.lives=self.lives.saturating_sub(1);
+        events.push(Event::PlayerDied{pos:self.player.pos,lives:self.lives});
+        if self.lives==0 {
+            self.game_over=true;
+            events.push(Event::GameOver{score:self.score});
+        } else {
+            self.respawning=true;
+            self.player.vel=Vec2::zero();
+        }
+    }
+    /// Picks when the next saucer shows up
+    fn schedule_saucer(&mut self) {
+        self.next_saucer_at=self.time+self.rng.gen_range(0.5..1.5)*self.config.saucer_interval;
+    }
+    fn destroy_saucer(&mut self,events:&mut Vec<Event>) {
+        if let Some(saucer)=self.saucer.take() {
+            events.push(Event::SaucerDestroyed{kind:saucer.kind,pos:saucer.pos});
+            self.schedule_saucer();
+        }
+    }
+    /// Spawns the saucer when it's due, then moves it and fires its gun
+    fn update_saucer(&mut self,events:&mut Vec<Event>) {
+        let delta=TICK;
+        let saucer=match &mut self.saucer {
+            Some(saucer)=>saucer,
+            None=>{
+                if self.time>=self.next_saucer_at&&!self.respawning {
+                    let kind=if self.rng.gen::<f32>()<self.config.small_saucer_chance {
+                        SaucerKind::Small
+                    } else {
+                        SaucerKind::Large
+                    };
+                    // Come in from the left or right edge and fly across once
+                    let right=self.rng.gen::<bool>();
+                    let pos=Vec2::new(if right {0.0} else {self.size.x},self.rng.gen_range(0.0..self.size.y));
+                    events.push(Event::SaucerSpawned{kind,pos});
+                    self.saucer=Some(Saucer {
+                        kind,
+                        pos,
+                        prev_pos:pos,
+                        vel:Vec2::new(if right {kind.speed()} else {-kind.speed()},0.0),
+                        range:self.size.x,
+                        next_shot:self.time+kind.fire_delay(),
+                        next_turn:self.time+SAUCER_TURN_TIME,
+                    });
+                }
+                return;
+            },
+        };
+        saucer.prev_pos=saucer.pos;
+        saucer.pos=wrap_position(saucer.pos+saucer.vel*delta,self.size);
+        saucer.range-=saucer.vel.x.abs()*delta;
+        if saucer.range<=0.0 {
+            // Flew off without being shot
+            self.saucer=None;
+            self.schedule_saucer();
+            return;
+        }
+        if self.time>=saucer.next_turn {
+            saucer.next_turn+=SAUCER_TURN_TIME;
+            saucer.vel.y=self.rng.gen_range(-1..=1) as f32*saucer.kind.speed()/2.0;
+        }
+        if self.time>=saucer.next_shot&&!self.respawning {
+            saucer.next_shot=self.time+saucer.kind.fire_delay();
+            let dir=match saucer.kind {
+                SaucerKind::Large=>Vec2::unit_x().rotated_z(self.rng.gen_range(0.0..std::f32::consts::TAU)),
+                SaucerKind::Small=>{
+                    let offset=collision::wrapped_offset(saucer.pos,self.player.pos,self.size);
+                    saucer::lead_target(offset,self.player.vel,SAUCER_BULLET_VEL)
+                },
+            };
+            let vel=dir*SAUCER_BULLET_VEL;
+            events.push(Event::SaucerShot{pos:saucer.pos,vel});
+            self.bullets.push(Bullet {
+                pos:saucer.pos,
+                prev_pos:saucer.pos,
+                vel,
+                dir:bullet_dir(vel),
+                owner:BulletOwner::Saucer,
+                expires_at:self.time+SAUCER_BULLET_LIFETIME,
+                range_left:f32::INFINITY,
+                piercing:false,
+            });
+        }
+    }
+    /// Collides player bullets and the player with the saucer, then saucer bullets with the
+    /// player. Bullets that hit are marked in `spent`.
+    fn collide_saucer(&mut self,spent:&mut [bool],player_hit:&mut bool,events:&mut Vec<Event>) {
+        let screen_scale=self.size/2.0;
+        let player_dir=self.player.dir;
+        let player_lines=|pos:Vec2|collision::transform(PLAYER_VECTOR,pos,screen_scale,player_dir);
+        let player_extent=collision::extent(PLAYER_VECTOR)*screen_scale.reduce_partial_max();
+        if let Some(&Saucer{kind,pos:saucer_pos,..})=self.saucer.as_ref() {
+            let scale=screen_scale*kind.scale();
+            let lines=collision::transform(SAUCER_VECTOR,saucer_pos,scale,0.0);
+            let extent=collision::extent(SAUCER_VECTOR)*scale.reduce_partial_max();
+            let mut shot=false;
+            let mut destroyed=false;
+            for (i,bullet) in self.bullets.iter().enumerate() {
+                if spent[i]||bullet.owner!=BulletOwner::Player {continue}
+                let step=collision::wrapped_offset(bullet.prev_pos,bullet.pos,self.size);
+                let offset=collision::wrapped_offset(saucer_pos,bullet.pos,self.size);
+                let pos=saucer_pos+offset;
+                if offset.magnitude_squared()<=(extent+step.magnitude()).powi(2)&&collision::segment_hits_lines(pos-step,pos,&lines) {
+                    spent[i]=true;
+                    shot=true;
+                    destroyed=true;
+                    break;
+                }
+            }
+            if !destroyed&&!*player_hit&&self.can_hit_player() {
+                let offset=collision::wrapped_offset(saucer_pos,self.player.pos,self.size);
+                if offset.magnitude_squared()<=(extent+player_extent).powi(2)&&collision::lines_overlap(&player_lines(saucer_pos+offset),&lines) {
+                    destroyed=true;
+                    *player_hit=true;
+                }
+            }
+            if shot {
+                self.award(self.config.scores.saucer(kind),saucer_pos,events);
+            }
+            if destroyed {
+                self.destroy_saucer(events);
+            }
+        }
+        // The shield soaks up saucer bullets
+        let shielded=self.player.shielded;
+        if *player_hit||!(shielded||self.can_hit_player()) {return}
+        let lines=player_lines(self.player.pos);
+        for (i,bullet) in self.bullets.iter().enumerate() {
+            if spent[i]||bullet.owner!=BulletOwner::Saucer {continue}
+            let step=collision::wrapped_offset(bullet.prev_pos,bullet.pos,self.size);
+            let offset=collision::wrapped_offset(self.player.pos,bullet.pos,self.size);
+            let pos=self.player.pos+offset;
+            if shielded {
+                if offset.magnitude()<=SHIELD_RADIUS {
+                    spent[i]=true;
+                    events.push(Event::ShieldBounce{pos:bullet.pos});
+                }
+            } else if offset.magnitude_squared()<=(player_extent+step.magnitude()).powi(2)&&collision::segment_hits_lines(pos-step,pos,&lines) {
+                spent[i]=true;
+                *player_hit=true;
+                return;
+            }
+        }
+    }
+    /// Where something moving from `prev` to `pos` is drawn `alpha` of the way through the next
+    /// tick. Moves across a screen edge take the short way, so the result can be slightly off
+    /// screen.
+    fn lerp_pos(&self,prev:Vec2,pos:Vec2,alpha:f32)->Vec2 {
+        return prev+collision::wrapped_offset(prev,pos,self.size)*alpha;
+    }
+    /// Draws the game `alpha` (0..1) of the way between the last two ticks
+    pub fn render(&mut self,buffer:&mut Buffer2d<u32>,alpha:f32) {
+        let mut vertices=Vec::new();
+        let mut lines=Vec::new();
+        for asteroid in self.asteroids.iter() {
+            let pos=self.lerp_pos(asteroid.prev_pos,asteroid.pos,alpha);
+            let pos=(pos/(self.size/2.0))-1.0;
+            let angle=asteroid.prev_angle+(asteroid.angle-asteroid.prev_angle)*alpha;
+            lines.clear();
+            for vtx in self.asteroid_vectors[asteroid.model_index].iter() {
+                lines.push(vtx.rotated_z(angle)*asteroid.radius/(self.size/2.0));
+            }
+            push_wrapped(&mut vertices,&lines,pos);
+        }
+        for bullet in self.bullets.iter() {
+            let pos=self.lerp_pos(bullet.prev_pos,bullet.pos,alpha);
+            let pos=(pos/(self.size/2.0))-1.0;
+            lines.clear();
+            for vtx in BULLET_VECTOR {
+                lines.push(vtx.rotated_z(bullet.dir));
+            }
+            push_wrapped(&mut vertices,&lines,pos);
+        }
+        if let Some(saucer)=&self.saucer {
+            let pos=self.lerp_pos(saucer.prev_pos,saucer.pos,alpha);
+            let pos=(pos/(self.size/2.0))-1.0;
+            lines.clear();
+            for vtx in SAUCER_VECTOR {
+                lines.push(vtx*saucer.kind.scale());
+            }
+            push_wrapped(&mut vertices,&lines,pos);
+        }
+        for powerup in self.powerups.iter() {
+            // Blink before disappearing
+            let left=powerup.expires_at-self.time;
+            if left<POWERUP_WARN_TIME&&(left*INVULNERABLE_BLINK_RATE) as u32%2==1 {continue}
+            let pos=self.lerp_pos(powerup.prev_pos,powerup.pos,alpha);
+            let pos=(pos/(self.size/2.0))-1.0;
+            lines.clear();
+            lines.extend_from_slice(POWERUP_FRAME);
+            lines.extend_from_slice(powerup.kind.icon());
+            push_wrapped(&mut vertices,&lines,pos);
+        }
+        // Blink while invulnerable
+        let hidden=self.is_invulnerable()&&((self.player.invulnerable_until-self.time)*INVULNERABLE_BLINK_RATE) as u32%2==1;
+        if !self.respawning&&!hidden {
+            let pos=self.lerp_pos(self.player.prev_pos,self.player.pos,alpha);
+            let pos=(pos/(self.size/2.0))-1.0;
+            let dir=self.player.prev_dir+(self.player.dir-self.player.prev_dir)*alpha;
+            lines.clear();
+            for vtx in PLAYER_VECTOR {
+                lines.push(vtx.rotated_z(dir));
+            }
+            if self.player.shielded {
+                let radius=Vec2::broadcast(SHIELD_RADIUS)/(self.size/2.0);
+                for i in 0..SHIELD_SEGMENTS {
+                    for j in [i,i+1] {
+                        let angle=j as f32/SHIELD_SEGMENTS as f32*std::f32::consts::TAU;
+                        lines.push(Vec2::new(angle.cos(),angle.sin())*radius);
+                    }
+                }
+            }
+            push_wrapped(&mut vertices,&lines,pos);
+        }
+        assert!(vertices.len()%2==0);
+        self.draw::<Lines<(f32,)>,_>(
+            &vertices,
+            buffer,
+            None,
+        );
+    }
+    /// Draws a ship icon for every remaining life in a row starting at `origin`, in pixels from the
+    /// top left of the buffer
+    pub fn render_lives(&mut self,buffer:&mut Buffer2d<u32>,origin:Vec2) {
+        let half=Vec2::new(buffer.size()[0] as f32,buffer.size()[1] as f32)/2.0;
+        let mut vertices=Vec::new();
+        for i in 0..self.lives {
+            let pixel=origin+Vec2::new(i as f32*LIFE_ICON_SPACING,0.0);
+            let pos=Vec2::new(pixel.x/half.x-1.0,1.0-pixel.y/half.y);
+            vertices.extend(PLAYER_VECTOR.iter().map(|vtx|vtx+pos));
+        }
+        self.draw::<Lines<(f32,)>,_>(
+            &vertices,
+            buffer,
+            None,
+        );
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_max_radius(radius:f32)->Config {
+        Config {
+            asteroid_max_radius:radius,
+            new_asteroid_min_radius:radius-10.0,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn asteroids_too_big_for_the_world_are_rejected() {
+        assert!(Game::with_config([1000.0,1000.0],1,config_with_max_radius(700.0)).is_err());
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        let config=Config {
+            new_asteroid_min_radius:80.0,
+            ..Config::default()
+        };
+        assert!(Game::with_config([1000.0,1000.0],1,config).is_err());
+        let mut config=Config::default();
+        config.waves[0].speed=100.0..100.0;
+        assert!(Game::with_config([1000.0,1000.0],1,config).is_err());
+    }
+
+    #[test]
+    fn the_biggest_allowed_asteroids_still_spawn() {
+        let max_radius=500.0-PLAYER_RADIUS-SAFE_SPAWN_MARGIN;
+        let game=Game::with_config([1000.0,1000.0],1,config_with_max_radius(max_radius)).unwrap();
+        assert!(!game.asteroids().is_empty());
+    }
+
+    #[test]
+    fn new_bullets_dont_hit_what_is_behind_the_ship() {
+        // A square whose near edge is half a bullet step behind the ship
+        let corner=0.5_f32.sqrt();
+        let square=vec![
+            Vec2::new(-corner,-corner),Vec2::new(corner,-corner),
+            Vec2::new(corner,-corner),Vec2::new(corner,corner),
+            Vec2::new(corner,corner),Vec2::new(-corner,corner),
+            Vec2::new(-corner,corner),Vec2::new(-corner,-corner),
+        ];
+        let config=Config::default();
+        let gap=config.bullet_speed*TICK/2.0;
+        let mut game=Game::with_shapes([1000.0,1000.0],1,config,vec![square]).unwrap();
+        game.player.dir=0.0;
+        game.player.vel=Vec2::zero();
+        game.player.invulnerable_until=f32::INFINITY;
+        game.player.last_shoot=f32::NEG_INFINITY;
+        let radius=50.0;
+        let pos=game.player.pos+Vec2::new(0.0,corner*radius+gap);
+        game.asteroids=vec![Asteroid {
+            pos,
+            prev_pos:pos,
+            vel:Vec2::zero(),
+            radius,
+            model_index:0,
+            angle:0.0,
+            prev_angle:0.0,
+            angular_vel:0.0,
+        }];
+        let events=game.tick(Buttons{fire:true,..Buttons::default()});
+        assert!(events.iter().any(|e|matches!(e,Event::Shot{..})));
+        assert!(!events.iter().any(|e|matches!(e,Event::AsteroidDestroyed{..})));
+        assert_eq!(game.asteroids().len(),1);
+    }
+
+    #[test]
+    fn rapid_fire_never_slows_the_gun_down() {
+        let config=Config {
+            shot_delay:0.02,
+            max_player_bullets:100,
+            ..Config::default()
+        };
+        let shots=|rapid_fire:bool|{
+            let mut game=Game::with_config([1000.0,1000.0],1,config.clone()).unwrap();
+            if rapid_fire {
+                game.player.powerups.push((PowerUpKind::RapidFire,f32::INFINITY));
+            }
+            let mut shots=0;
+            for _ in 0..TICK_RATE {
+                shots+=game.tick(Buttons{fire:true,..Buttons::default()})
+                    .iter()
+                    .filter(|e|matches!(e,Event::Shot{..}))
+                    .count();
+            }
+            shots
+        };
+        assert_eq!(shots(true),shots(false));
+    }
+
+    #[test]
+    fn spread_shots_stay_under_the_bullet_cap() {
+        let config=Config {
+            max_player_bullets:2,
+            ..Config::default()
+        };
+        let mut game=Game::with_config([1000.0,1000.0],1,config).unwrap();
+        game.player.powerups.push((PowerUpKind::Spread,f32::INFINITY));
+        let mut most=0;
+        for _ in 0..TICK_RATE {
+            game.tick(Buttons{fire:true,..Buttons::default()});
+            most=most.max(game.bullets().iter().filter(|b|b.owner==BulletOwner::Player).count());
+        }
+        assert_eq!(most,2);
+    }
+}
@@ -1,20 +1,19 @@
-//! Asteroid vectors created by me. Link: https://vectr.com/design/editor/4af10ec0-ce81-4622-8e33-72842af687f0
+//! Window, keyboard and audio frontend over the `asteroids` simulation crate.
+
+#![allow(clippy::needless_return)]
 
 
 use minifb::{
     Window,
-    Key,
+    KeyRepeat,
     MouseMode,
     MouseButton,
 };
 use euc::{
     buffer::Buffer2d,
-    rasterizer::Lines,
     Target,
-    Pipeline,
 };
 use rand::{
-    rngs::ThreadRng,
     Rng,
     thread_rng,
 };
@@ -42,81 +41,68 @@ use bitmap_font::{
     tamzen::FONT_10x20,
     TextStyle,
 };
-use rodio::{
-    source::Buffered,
-    OutputStream,
-    OutputStreamHandle,
-    Sink,
-    Decoder,
-    Source,
-};
 use std::{
     time::{
         Duration,
         Instant,
     },
     ops::{
-        Range,
         Deref,
         DerefMut,
     },
-    fs::{
-        read_to_string,
-        read_dir,
-        File,
+    fs::read_to_string,
+    path::PathBuf,
+};
+use asteroids::{
+    asset::{
+        self,
+        AssetError,
+        AssetPath,
+        AssetWatcher,
+    },
+    headless::{
+        self,
+        Script,
     },
-    io::BufReader,
-    collections::HashMap,
+    config::{
+        Config,
+        GameMode,
+    },
+    flight::FlightModel,
+    Game,
+    Event,
+    Vec2,
+    TICK,
+};
+use toml::Value;
+use audio::Audio;
+use controls::ControlsScreen;
+use settings::{
+    Action,
+    KeyMap,
+    SettingsFile,
+    RESERVED_KEY,
+    key_name,
 };
-use svg_to_vector::*;
-
-
-mod svg_to_vector;
 
 
-pub type Vec2=vek::Vec2<f32>;
+mod audio;
+mod controls;
+mod settings;
 
 
-const FPS:usize=30;
-const DISABLE_GAME_OVER:bool=false;
-const PLAYER_ACCEL:f32=600.0;        // m/s/s
-const PLAYER_ROTATE_VEL:f32=7.0;     // deg/s
-const PLAYER_RADIUS:f32=10.0;
-const BULLET_VEL:f32=1000.0;
-const BULLET_DELAY:Duration=Duration::from_millis(100);
-const KEYMAP:KeyMap=KeyMap {
-    forward:Key::W,
-    backward:Key::S,
-    left:Key::A,
-    right:Key::D,
-    fire:Key::F,
-};
-const MAX_ASTEROIDS:usize=50;
-const ASTEROID_MAX_RADIUS:f32=80.0;
-const ASTEROID_MIN_RADIUS:f32=30.0;
-const NEW_ASTEROID_MIN_RADIUS:f32=40.0;
-const PLAYER_VECTOR:&[Vec2]=&[
-    Vec2::new(0.0,-0.02),
-    Vec2::new(0.01,0.01),
-    Vec2::new(0.0,-0.02),
-    Vec2::new(-0.01,0.01),
-    Vec2::new(-0.01,0.01),
-    Vec2::new(-0.02,0.02),
-    Vec2::new(0.01,0.01),
-    Vec2::new(0.02,0.02),
-    Vec2::new(0.01,0.01),
-    Vec2::new(-0.01,0.01),
-    Vec2::new(0.01,0.01),
-    Vec2::new(0.0,0.02),
-    Vec2::new(-0.01,0.01),
-    Vec2::new(0.0,0.02),
-];
-const BULLET_VECTOR:&[Vec2]=&[
-    Vec2::new(0.0,-0.005),
-    Vec2::new(0.005,0.005),
-    Vec2::new(0.0,-0.005),
-    Vec2::new(-0.005,0.005),
-];
+/// Environment variable with extra asset directories, searched after `--assets`
+const ASSETS_VAR:&str="ASTEROIDS_ASSETS";
+/// Seconds between checks of the asset directories for changed files
+const RELOAD_INTERVAL:f32=0.5;
+/// Longest frame time fed to the simulation, so a stall doesn't turn into hundreds of ticks
+const MAX_FRAME_TIME:f32=0.25;
+/// Seconds the "Wave N" banner stays up at the start of a wave
+const WAVE_BANNER_TIME:f32=2.0;
+/// Seconds a score popup stays up
+const POPUP_TIME:f32=1.0;
+/// Pixels a score popup floats up over its lifetime
+const POPUP_RISE:f32=40.0;
 
 
 struct Buffer(pub Buffer2d<u32>);
@@ -159,371 +145,362 @@ impl DrawTarget for Buffer {
         return Ok(());
     }
 }
-struct KeyMap {
-    forward:Key,
-    backward:Key,
-    left:Key,
-    right:Key,
-    fire:Key,
-}
-struct Buttons {
-    forward:bool,
-    backward:bool,
-    left:bool,
-    right:bool,
-    fire:bool,
-}
-struct Asteroid {
-    pos:Vec2,
-    vel:Vec2,
-    radius:f32,
-    model_index:usize,
-}
-struct Bullet {
+
+
+/// Size of the HUD meters in pixels
+const METER_SIZE:Size=Size::new(100,10);
+/// Points floating up from where something was destroyed
+struct Popup {
+    text:String,
+    /// Pixels from the top left of the buffer
     pos:Vec2,
-    vel:Vec2,
-    dir:f32,
+    age:f32,
 }
-struct Player {
-    pos:Vec2,
-    vel:Vec2,
-    dir:f32,
-    last_shoot:Instant,
+
+
+/// Draws `label` and a bar `fraction` full next to it, starting at `origin` in pixels from the top
+/// left
+fn draw_meter(buffer:&mut Buffer,label:&str,origin:Point,fraction:f32) {
+    Text::new(label,origin,TextStyle::new(&FONT_10x20, BinaryColor::On))
+        .draw(buffer).unwrap();
+    let bar=origin+Point::new(70,5);
+    let outline=PrimitiveStyleBuilder::new()
+        .stroke_color(BinaryColor::On)
+        .stroke_width(1)
+        .build();
+    Rectangle::new(bar,METER_SIZE)
+        .into_styled(outline)
+        .draw(buffer)
+        .unwrap();
+    let fill=PrimitiveStyleBuilder::new()
+        .fill_color(BinaryColor::On)
+        .build();
+    Rectangle::new(bar,Size::new((METER_SIZE.width as f32*fraction) as u32,METER_SIZE.height))
+        .into_styled(fill)
+        .draw(buffer)
+        .unwrap();
 }
-struct Game {
-    asteroids:Vec<Asteroid>,
-    bullets:Vec<Bullet>,
-    player:Player,
-    game_over:bool,
-    score:u64,
-    size:Vec2,
-    asteroid_vectors:Vec<Vec<Vec2>>,
-    asteroid_vector_range:Range<usize>,
-    rng:ThreadRng,
-    _stream:OutputStream,
-    stream_handle:OutputStreamHandle,
-    sinks:Vec<Sink>,
-    game_over_sink:Sink,
-    collision_sound:Buffered<Decoder<BufReader<File>>>,
-    shoot_sound:Buffered<Decoder<BufReader<File>>>,
-    game_over_sound:Buffered<Decoder<BufReader<File>>>,
+
+
+/// What the frontend runs with, from the settings file and the command line
+struct Settings {
+    /// Settings file the key bindings are saved to
+    path:PathBuf,
+    fps:usize,
+    keys:KeyMap,
+    game:Config,
 }
-impl Pipeline for Game {
-    type Vertex=Vec2;
-    type VsOut=();
-    type Pixel=u32;
-    fn vert(&self,pos:&Self::Vertex)->([f32;4],Self::VsOut) {
-        return ([pos.x,pos.y,0.0,1.0],());
+impl Settings {
+    /// The settings as a settings file, game config and all
+    fn to_toml(&self)->Result<String,String> {
+        let mut value=Value::try_from(SettingsFile {
+            fps:self.fps,
+            keys:self.keys.clone(),
+            game:Default::default(),
+        }).map_err(|e|e.to_string())?;
+        value["game"]=Value::try_from(&self.game).map_err(|e|e.to_string())?;
+        shorten_floats(&mut value);
+        return toml::to_string(&value).map_err(|e|e.to_string());
     }
-    fn frag(&self,_:&Self::VsOut)->Self::Pixel {
-        u32::from_le_bytes([200,200,200,255])   // light grey
+}
+/// Rounds every float to the shortest text that reads back as the same `f32`, so `0.1` isn't
+/// written as `0.10000000149011612`
+fn shorten_floats(value:&mut Value) {
+    match value {
+        Value::Float(f)=>*f=(*f as f32).to_string().parse().unwrap_or(*f),
+        Value::Table(table)=>table.iter_mut().for_each(|(_,value)|shorten_floats(value)),
+        Value::Array(array)=>array.iter_mut().for_each(shorten_floats),
+        _=>{},
     }
 }
-impl Game {
-    fn new(size:[f32;2])->Game {
-        let collision_sound=Decoder::new_wav(BufReader::new(File::open("assets/sounds/asteroid_collision.wav").unwrap())).unwrap().buffered();
-        let shoot_sound=Decoder::new_wav(BufReader::new(File::open("assets/sounds/shoot.wav").unwrap())).unwrap().buffered();
-        let game_over_sound=Decoder::new_wav(BufReader::new(File::open("assets/sounds/game_over.wav").unwrap())).unwrap().buffered();
-        let (stream,stream_handle)=OutputStream::try_default().unwrap();
-        let sinks=vec![Sink::try_new(&stream_handle).unwrap()];
-        let game_over_sink=Sink::try_new(&stream_handle).unwrap();
-        let mut asteroid_vectors=Vec::new();
-        for file in read_dir("assets/asteroids").unwrap() {
-            let file=file.unwrap();
-            let string=read_to_string(file.path()).unwrap();
-            if let Some(vector)=svg_to_vector(&string) {
-                asteroid_vectors.push(vector);
-            }
-        }
-        let mut rng=thread_rng();
-        let mut asteroid=Asteroid {
-            pos:Vec2::new(rng.gen_range(0.0..1000.0),rng.gen_range(0.0..1000.0)),
-            vel:Vec2::new(rng.gen_range(50.0..200.0),rng.gen_range(50.0..200.0)),
-            radius:rng.gen_range(NEW_ASTEROID_MIN_RADIUS..ASTEROID_MAX_RADIUS),
-            model_index:rng.gen_range(0..asteroid_vectors.len()),
+
+
+/// Command line options
+struct Args {
+    /// Run this many simulated seconds without a window or audio, then print the result
+    headless:Option<f32>,
+    /// Scripted input for headless mode
+    script:Script,
+    /// Seed for the first game. Random if not given.
+    seed:Option<u64>,
+    /// Game mode. The settings file's, or classic, if not given.
+    mode:Option<GameMode>,
+    /// Flight model preset. The mode's own one if not given.
+    flight:Option<FlightModel>,
+    /// Settings file. `settings::default_path()` when not given.
+    config_path:Option<String>,
+    /// Render rate over the settings file's
+    fps:Option<usize>,
+    /// `key=value` pairs applied to the game config last, like `lives=5` or `flight.drag=0.2`
+    overrides:Vec<String>,
+    /// Print the settings in effect as a settings file and exit
+    print_config:bool,
+    /// Directories searched for assets first, in order
+    asset_dirs:Vec<PathBuf>,
+}
+impl Args {
+    fn parse()->Result<Args,String> {
+        let mut out=Args {
+            headless:None,
+            script:Script::new(),
+            seed:None,
+            mode:None,
+            flight:None,
+            config_path:None,
+            fps:None,
+            overrides:Vec::new(),
+            print_config:false,
+            asset_dirs:Vec::new(),
         };
-        while asteroid.pos.distance(Vec2::new(500.0,500.0))<asteroid.radius+PLAYER_RADIUS+50.0 {
-            asteroid.pos=Vec2::new(rng.gen_range(0.0..1000.0),rng.gen_range(0.0..1000.0));
-        }
-        Game {
-            _stream:stream,
-            collision_sound,
-            shoot_sound,
-            game_over_sound,
-            stream_handle,
-            game_over_sink,
-            sinks,
-            asteroid_vector_range:0..asteroid_vectors.len(),
-            asteroids:vec![asteroid],
-            bullets:Vec::new(),
-            player:Player {
-                dir:0.0,
-                pos:Vec2::new(500.0,500.0),
-                vel:Vec2::zero(),
-                last_shoot:Instant::now(),
-            },
-            game_over:false,
-            score:0,
-            size:Vec2::new(size[0],size[1]),
-            asteroid_vectors,
-            rng,
+        let mut args=std::env::args().skip(1);
+        while let Some(arg)=args.next() {
+            match arg.as_str() {
+                "--headless"=>{
+                    let secs=args.next().ok_or("--headless expects a number of seconds")?;
+                    out.headless=Some(secs.parse().map_err(|_|format!("Invalid number of seconds: `{}`",secs))?);
+                },
+                "--script"=>{
+                    let path=args.next().ok_or("--script expects a file path")?;
+                    let source=read_to_string(&path).map_err(|e|format!("Could not read `{}`: {}",path,e))?;
+                    out.script=Script::parse(&source).map_err(|e|format!("{}: {}",path,e))?;
+                },
+                "--seed"=>{
+                    let seed=args.next().ok_or("--seed expects a number")?;
+                    out.seed=Some(seed.parse().map_err(|_|format!("Invalid seed: `{}`",seed))?);
+                },
+                "--mode"=>{
+                    let mode=args.next().ok_or("--mode expects classic or barrage")?;
+                    out.mode=Some(mode.parse()?);
+                },
+                "--flight"=>{
+                    let flight=args.next().ok_or("--flight expects arcade or newtonian")?;
+                    out.flight=Some(flight.parse()?);
+                },
+                "--config"=>{
+                    out.config_path=Some(args.next().ok_or("--config expects a file path")?);
+                },
+                "--fps"=>{
+                    let fps=args.next().ok_or("--fps expects a number")?;
+                    match fps.parse() {
+                        Ok(fps) if fps>0=>out.fps=Some(fps),
+                        _=>return Err(format!("Invalid fps: `{}`",fps)),
+                    }
+                },
+                "--set"=>{
+                    out.overrides.push(args.next().ok_or("--set expects key=value")?);
+                },
+                "--print-config"=>out.print_config=true,
+                "--assets"=>{
+                    out.asset_dirs.push(args.next().ok_or("--assets expects a directory")?.into());
+                },
+                _=>return Err(format!("Unknown argument: `{}`",arg)),
+            }
         }
+        return Ok(out);
     }
-    fn reset(&mut self) {
-        let mut asteroid=Asteroid {
-            pos:Vec2::new(self.rng.gen_range(0.0..1000.0),self.rng.gen_range(0.0..1000.0)),
-            vel:Vec2::new(self.rng.gen_range(50.0..200.0),self.rng.gen_range(50.0..200.0)),
-            radius:self.rng.gen_range(NEW_ASTEROID_MIN_RADIUS..ASTEROID_MAX_RADIUS),
-            model_index:self.rng.gen_range(self.asteroid_vector_range.clone()),
-        };
-        while asteroid.pos.distance(self.player.pos)<asteroid.radius+PLAYER_RADIUS+50.0 {
-            asteroid.pos=Vec2::new(self.rng.gen_range(0.0..1000.0),self.rng.gen_range(0.0..1000.0));
+    /// Where assets are looked for: every `--assets`, then the directories in `ASSETS_VAR`, then
+    /// the user's data directory, before the built-in ones
+    fn asset_path(&self)->AssetPath {
+        let mut dirs=self.asset_dirs.clone();
+        if let Some(var)=std::env::var_os(ASSETS_VAR) {
+            dirs.extend(std::env::split_paths(&var).filter(|dir|!dir.as_os_str().is_empty()));
         }
-        self.asteroids=vec![asteroid];
-        self.bullets=Vec::new();
-        self.player=Player {
-            dir:0.0,
-            pos:Vec2::new(500.0,500.0),
-            vel:Vec2::zero(),
-            last_shoot:Instant::now(),
-        };
-        self.game_over=false;
-        self.score=0;
+        dirs.extend(settings::user_dir("XDG_DATA_HOME",".local/share"));
+        return AssetPath::new(dirs);
     }
-    fn is_game_over(&self)->bool {self.game_over}
-    /// Processes the frame update and returns true while the game is running, and false if the
-    /// player gets hit
-    fn tick(&mut self,buttons:Buttons,delta:f32)->bool {
-        // short-circuit and process nothing if we are in the game over state
-        if self.game_over {return false}
-        for a in self.asteroids.iter_mut() {
-            a.pos+=a.vel*delta;
-            if a.pos.x>=self.size.x {
-                a.pos.x=0.0;
-            } else if a.pos.x<0.0 {
-                a.pos.x=self.size.x;
-            }
-            if a.pos.y>=self.size.y {
-                a.pos.y=0.0;
-            } else if a.pos.y<0.0 {
-                a.pos.y=self.size.y;
-            }
-        }
-        for b in self.bullets.iter_mut() {
-            b.pos+=b.vel*delta;
-            if b.pos.x>=self.size.x {
-                b.pos.x=0.0;
-            } else if b.pos.x<0.0 {
-                b.pos.x=self.size.x;
-            }
-            if b.pos.y>=self.size.y {
-                b.pos.y=0.0;
-            } else if b.pos.y<0.0 {
-                b.pos.y=self.size.y;
-            }
-        }
-        self.player.pos+=self.player.vel*delta;
-        if buttons.left {
-            self.player.dir+=PLAYER_ROTATE_VEL.to_radians();
-        }
-        if buttons.right {
-            self.player.dir-=PLAYER_ROTATE_VEL.to_radians();
-        }
-        if buttons.forward {
-            self.player.vel-=Vec2::new(0.0,PLAYER_ACCEL*delta).rotated_z(self.player.dir);
-        }
-        if buttons.backward {
-            self.player.vel-=Vec2::new(0.0,-PLAYER_ACCEL*delta).rotated_z(self.player.dir);
-        }
-        if self.player.pos.x>=self.size.x {
-            self.player.pos.x=0.0;
-        } else if self.player.pos.x<0.0 {
-            self.player.pos.x=self.size.x;
-        }
-        if self.player.pos.y>=self.size.y {
-            self.player.pos.y=0.0;
-        } else if self.player.pos.y<0.0 {
-            self.player.pos.y=self.size.y;
+    /// Loads the settings file and applies the options on top. The game config is built from the
+    /// mode, then the file, then `--flight`, then every `--set`.
+    fn settings(&self)->Result<Settings,String> {
+        let path=self.config_path.as_ref().map(PathBuf::from).unwrap_or_else(settings::default_path);
+        let mut file=SettingsFile::load(&path,self.config_path.is_some())?;
+        let name=path.display();
+        let file_mode=match file.game.remove("mode") {
+            Some(Value::String(mode))=>Some(mode.parse::<GameMode>().map_err(|e|format!("{}: {}",name,e))?),
+            Some(_)=>return Err(format!("{}: `game.mode` must be a string",name)),
+            None=>None,
+        };
+        let mode=self.mode.or(file_mode).unwrap_or(GameMode::Classic);
+        let mut config=Config::for_mode(mode).merge(Value::Table(file.game))
+            .map_err(|e|format!("{}: in [game]: {}",name,e))?;
+        if let Some(flight)=&self.flight {
+            config.flight=flight.clone();
         }
-        if buttons.fire {
-            if self.player.last_shoot.elapsed()>=BULLET_DELAY {
-                let mut played=false;
-                for sink in self.sinks.iter_mut() {
-                    if sink.empty() {
-                        sink.append(self.shoot_sound.clone());
-                        played=true;
-                        break;
-                    }
-                }
-                if !played {
-                    let sink=Sink::try_new(&self.stream_handle).unwrap();
-                    sink.append(self.shoot_sound.clone());
-                    self.sinks.push(sink);
-                }
-                self.player.last_shoot=Instant::now();
-                self.bullets.push(Bullet {
-                    pos:self.player.pos,
-                    vel:self.player.vel-Vec2::new(0.0,BULLET_VEL).rotated_z(self.player.dir),
-                    dir:self.player.dir,
-                });
-            }
+        for set in self.overrides.iter() {
+            let value=toml::from_str(set).map_err(|e|format!("Invalid --set `{}`: {}",set,e))?;
+            config=config.merge(value).map_err(|e|format!("--set `{}`: {}",set,e))?;
         }
-        let mut new_asteroids=Vec::new();
-        let mut asteroid_count=self.asteroids.len();
-        self.asteroids.retain(|asteroid|{
-            // Collide asteroid-bullet then delete the asteroid and bullet if they collide
-            let mut hit=false;
-            let mut idx=0;
-            for (i,bullet) in self.bullets.iter().enumerate() {
-                let dist_sq=asteroid.pos.distance_squared(bullet.pos);
-                if dist_sq<=(asteroid.radius*asteroid.radius) {
-                    hit=true;
-                    idx=i;
-                    break;
-                }
-            }
-            // Collide asteroid-player then set game over
-            if !self.game_over&&!DISABLE_GAME_OVER {
-                let player_dist_sq=asteroid.pos.distance_squared(self.player.pos);
-                if player_dist_sq<=(asteroid.radius+PLAYER_RADIUS).powi(2) {
-                    self.game_over=true;
-                    self.game_over_sink.append(self.game_over_sound.clone());
-                }
-            }
-            // Remove the bullet if it was a hit
-            if hit {
-                let mut played=false;
-                for sink in self.sinks.iter_mut() {
-                    if sink.empty() {
-                        sink.append(self.collision_sound.clone());
-                        played=true;
-                        break;
-                    }
-                }
-                if !played {
-                    let sink=Sink::try_new(&self.stream_handle).unwrap();
-                    sink.append(self.collision_sound.clone());
-                    self.sinks.push(sink);
-                }
-                asteroid_count-=1;
-                self.bullets.remove(idx);
-                self.score+=1;
-                if asteroid_count<MAX_ASTEROIDS {
-                    if asteroid.radius>ASTEROID_MIN_RADIUS {
-                        let amt=self.rng.gen_range(2..=4);
-                        let radius=asteroid.radius/(amt as f32);
-                        asteroid_count+=amt;
-                        for _ in 0..amt {
-                            new_asteroids.push(Asteroid {
-                                pos:asteroid.pos+Vec2::new(self.rng.gen_range(0.0..radius),self.rng.gen_range(0.0..radius)),
-                                vel:asteroid.vel+Vec2::new(self.rng.gen_range(10.0..75.0),self.rng.gen_range(10.0..75.0)),
-                                model_index:self.rng.gen_range(self.asteroid_vector_range.clone()),
-                                radius,
-                            });
-                        }
-                    } else {
-                        let mut asteroid=Asteroid {
-                            pos:Vec2::new(self.rng.gen_range(0.0..1000.0),self.rng.gen_range(0.0..1000.0)),
-                            vel:Vec2::new(self.rng.gen_range(50.0..200.0),self.rng.gen_range(50.0..200.0)),
-                            radius:self.rng.gen_range(NEW_ASTEROID_MIN_RADIUS..ASTEROID_MAX_RADIUS),
-                            model_index:self.rng.gen_range(self.asteroid_vector_range.clone()),
-                        };
-                        while asteroid.pos.distance(self.player.pos)<asteroid.radius+PLAYER_RADIUS+50.0 {
-                            asteroid.pos=Vec2::new(self.rng.gen_range(0.0..1000.0),self.rng.gen_range(0.0..1000.0));
-                        }
-                        new_asteroids.push(asteroid);
-                    }
-                }
-            }
-            !hit
+        return Ok(Settings {
+            path:path.clone(),
+            fps:self.fps.unwrap_or(file.fps),
+            keys:file.keys,
+            game:config,
         });
-        self.asteroids.append(&mut new_asteroids);
-        // Collide asteroid-asteroid in a non-physical way that cheats
-        let mut collisions=HashMap::new();
-        for (a,asteroid_a) in self.asteroids.iter().enumerate() {
-            for (b,asteroid_b) in self.asteroids.iter().enumerate() {
-                if a!=b {
-                    let dist=asteroid_a.pos.distance(asteroid_b.pos);
-                    if dist<=(asteroid_a.radius+asteroid_b.radius) {
-                        let angle=asteroid_a.vel.angle_between(asteroid_b.vel);
-                        let move_vec=(asteroid_a.pos-asteroid_b.pos).normalized();
-                        let move_amt=dist-(asteroid_a.radius+asteroid_b.radius);
-                        let sq_sum=asteroid_a.radius.powi(2)+asteroid_b.radius.powi(2);
-                        let ratio_a=asteroid_a.radius.powi(2)/sq_sum;
-                        let ratio_b=asteroid_b.radius.powi(2)/sq_sum;
-                        collisions.insert(a,(-(angle*ratio_b),-move_vec*move_amt));
-                        collisions.insert(b,((angle*ratio_a),move_vec*move_amt));
-                    }
-                }
-            }
-        }
-        for (i,(angle,move_amt)) in collisions {
-            self.asteroids[i].vel.rotate_z(angle);
-            self.asteroids[i].pos+=move_amt;
-        }
-        !self.game_over
     }
-    fn render(&mut self,buffer:&mut Buffer2d<u32>) {
-        let mut vertices=Vec::new();
-        for asteroid in self.asteroids.iter() {
-            let pos=asteroid.pos;
-            let pos=(pos/(self.size/2.0))-1.0;
-            for vtx in self.asteroid_vectors[asteroid.model_index].iter() {
-                let vtx=(vtx*(asteroid.radius/4.0))/10000.0;
-                let res=vtx+pos;
-                vertices.push(res);
-            }
-        }
-        for bullet in self.bullets.iter() {
-            let pos=bullet.pos;
-            let pos=(pos/(self.size/2.0))-1.0;
-            for vtx in BULLET_VECTOR {
-                let res=(vtx.rotated_z(bullet.dir))+pos;
-                vertices.push(res);
-            }
-        }
-        let pos=self.player.pos;
-        let pos=(pos/(self.size/2.0))-1.0;
-        for vtx in PLAYER_VECTOR {
-            let res=(vtx.rotated_z(self.player.dir))+pos;
-            vertices.push(res);
-        }
-        assert!(vertices.len()%2==0);
-        self.draw::<Lines<(f32,)>,_>(
-            &vertices,
-            buffer,
-            None,
-        );
+}
+
+
+/// Lists the assets that failed to load and what's used instead
+fn report_assets(errors:&[AssetError]) {
+    if errors.is_empty() {
+        return;
+    }
+    eprintln!("Some assets failed to load:");
+    for error in errors.iter() {
+        eprintln!("    {}",error);
     }
 }
 
 
+/// Shown before the game starts: what every action is bound to
+fn start_text(keys:&KeyMap)->String {
+    let mut lines=Action::ALL.iter()
+        .map(|&action|{
+            let names=keys.keys(action)
+                .iter()
+                .map(|key|key_name(*key))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            format!("{} for {}",names,action.name())
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!("{} for controls",key_name(RESERVED_KEY)));
+    return lines.join("\n");
+}
+
+
 fn main() {
+    let args=match Args::parse() {
+        Ok(args)=>args,
+        Err(e)=>{
+            eprintln!("{}",e);
+            eprintln!("Usage: asteroids [--seed SEED] [--mode classic|barrage] [--flight arcade|newtonian] [--config FILE] [--set KEY=VALUE]... [--fps FPS] [--assets DIR]... [--print-config] [--headless SECONDS [--script FILE]]");
+            std::process::exit(1);
+        },
+    };
+    let mut settings=match args.settings() {
+        Ok(settings)=>settings,
+        Err(e)=>{
+            eprintln!("{}",e);
+            std::process::exit(1);
+        },
+    };
+    if args.print_config {
+        match settings.to_toml() {
+            Ok(toml)=>print!("{}",toml),
+            Err(e)=>{
+                eprintln!("{}",e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
+    let seed=args.seed.unwrap_or_else(||thread_rng().gen());
+    let assets=args.asset_path();
+    let mut asset_errors=Vec::new();
+    let shapes=asset::load_asteroid_shapes(&assets,settings.game.curve_tolerance,&mut asset_errors);
+    let mut game=match Game::with_shapes([1000.0,1000.0],seed,settings.game.clone(),shapes) {
+        Ok(game)=>game,
+        Err(e)=>{
+            eprintln!("{}",e);
+            std::process::exit(1);
+        },
+    };
+    if let Some(seconds)=args.headless {
+        report_assets(&asset_errors);
+        let report=headless::run(&mut game,seconds,&args.script);
+        println!("{}",report);
+        return;
+    }
     let mut buffer=Buffer(Buffer2d::new([1000,1000],0u32));
     let mut window=Window::new("Asteroids",1000,1000,Default::default()).unwrap();
-    window.limit_update_rate(Some(Duration::from_secs_f32(1.0/(FPS as f32))));
-    let mut last_frame=Instant::now();
-    let mut game=Game::new([1000.0,1000.0]);
-    Text::with_text_style("WASD to move\nF to fire",Point::new(500,500),TextStyle::new(&FONT_10x20, BinaryColor::On),EgTextStyle::with_alignment(Alignment::Center))
+    window.limit_update_rate(Some(Duration::from_secs_f32(1.0/(settings.fps as f32))));
+    let mut audio=Audio::new(&assets,&mut asset_errors);
+    report_assets(&asset_errors);
+    eprintln!("Seed: {}",game.seed());
+    Text::with_text_style(&start_text(&settings.keys),Point::new(500,500),TextStyle::new(&FONT_10x20, BinaryColor::On),EgTextStyle::with_alignment(Alignment::Center))
         .draw(&mut buffer).unwrap();
-    window.update_with_buffer(&buffer.0.as_ref(),1000,1000).unwrap();
+    window.update_with_buffer(buffer.0.as_ref(),1000,1000).unwrap();
     std::thread::sleep(Duration::from_secs(2));
+    let mut last_frame=Instant::now();
+    // Simulated time we still owe the game. Ticks are fixed length, so whatever is left over is
+    // used to interpolate the render.
+    let mut accumulator=0.0;
+    let mut popups:Vec<Popup>=Vec::new();
+    // The game is paused while this is open
+    let mut controls:Option<ControlsScreen>=None;
+    // Shapes and sounds are reloaded when their files change, so they can be edited while playing
+    let mut shape_watcher=AssetWatcher::new(&assets,asset::SHAPE_DIR);
+    let mut sound_watcher=AssetWatcher::new(&assets,asset::SOUND_DIR);
+    let mut last_reload_check=Instant::now();
     while window.is_open() {
         buffer.0.clear(0);
-        let elapsed=last_frame.elapsed();
+        let frame_time=last_frame.elapsed().as_secs_f32().min(MAX_FRAME_TIME);
         last_frame=Instant::now();
-        game.tick(
-            Buttons {
-                forward:window.is_key_down(KEYMAP.forward),
-                backward:window.is_key_down(KEYMAP.backward),
-                left:window.is_key_down(KEYMAP.left),
-                right:window.is_key_down(KEYMAP.right),
-                fire:window.is_key_down(KEYMAP.fire),
+        if last_reload_check.elapsed().as_secs_f32()>=RELOAD_INTERVAL {
+            last_reload_check=Instant::now();
+            let mut errors=Vec::new();
+            if shape_watcher.poll(&assets) {
+                game.set_shapes(asset::load_asteroid_shapes(&assets,game.config().curve_tolerance,&mut errors));
+                eprintln!("Reloaded asteroid shapes");
+            }
+            if sound_watcher.poll(&assets) {
+                audio.reload(&assets,&mut errors);
+                eprintln!("Reloaded sounds");
+            }
+            report_assets(&errors);
+        }
+        match &mut controls {
+            Some(screen)=>if screen.update(&window,&mut settings.keys) {
+                controls=None;
+                if let Err(e)=settings::save_keys(&settings.path,&settings.keys) {
+                    eprintln!("{}",e);
+                }
+            },
+            None=>if window.is_key_pressed(RESERVED_KEY,KeyRepeat::No) {
+                controls=Some(ControlsScreen::new());
             },
-            elapsed.as_secs_f32(),
-        );
-        game.render(&mut buffer.0);
-        Text::new(&format!("Score: {}",game.score), Point::zero(), TextStyle::new(&FONT_10x20, BinaryColor::On))
+        }
+        let frame_time=if controls.is_some() {0.0} else {frame_time};
+        accumulator+=frame_time;
+        let buttons=settings.keys.buttons(|key|window.is_key_down(key));
+        while accumulator>=TICK {
+            for event in game.tick(buttons) {
+                audio.handle(&event);
+                if let Event::Scored{points,pos}=event {
+                    popups.push(Popup {
+                        text:points.to_string(),
+                        pos:Vec2::new(pos.x,game.size().y-pos.y),
+                        age:0.0,
+                    });
+                }
+            }
+            accumulator-=TICK;
+        }
+        game.render(&mut buffer.0,accumulator/TICK);
+        popups.retain_mut(|popup|{
+            popup.age+=frame_time;
+            popup.age<POPUP_TIME
+        });
+        for popup in popups.iter() {
+            let pos=popup.pos-Vec2::new(0.0,popup.age/POPUP_TIME*POPUP_RISE);
+            Text::with_text_style(&popup.text,Point::new(pos.x as i32,pos.y as i32),TextStyle::new(&FONT_10x20, BinaryColor::On),EgTextStyle::with_alignment(Alignment::Center))
+                .draw(&mut buffer).unwrap();
+        }
+        let score=format!("Score: {}",game.score());
+        Text::new(&score, Point::zero(), TextStyle::new(&FONT_10x20, BinaryColor::On))
+            .draw(&mut buffer).unwrap();
+        game.render_lives(&mut buffer.0,Vec2::new(score.len() as f32*10.0+20.0,10.0));
+        draw_meter(&mut buffer,"Shield",Point::new(0,25),game.shield_energy());
+        draw_meter(&mut buffer,"Hyper",Point::new(0,45),game.hyperspace_charge());
+        for (i,(kind,left)) in game.powerup_timers().enumerate() {
+            let fraction=left/game.config().powerup_duration;
+            draw_meter(&mut buffer,kind.name(),Point::new(0,65+i as i32*20),fraction);
+        }
+        if game.wave_time()<WAVE_BANNER_TIME&&!game.is_game_over() {
+            Text::with_text_style(&format!("Wave {}",game.wave()),Point::new(500,300),TextStyle::new(&FONT_10x20, BinaryColor::On),EgTextStyle::with_alignment(Alignment::Center))
+                .draw(&mut buffer).unwrap();
+        }
+        Text::with_text_style(&format!("Seed: {}",game.seed()),Point::new(1000,0),TextStyle::new(&FONT_10x20, BinaryColor::On),EgTextStyle::with_alignment(Alignment::Right))
             .draw(&mut buffer).unwrap();
         if game.is_game_over() {
             let style=PrimitiveStyleBuilder::new()
@@ -544,10 +521,15 @@ fn main() {
                 if let Some((x,y))=window.get_mouse_pos(MouseMode::Discard) {
                     if (x<600.0&&x>400.0)&&(y<558.0&&y>482.0) {   // if in the button
                         game.reset();
+                        popups.clear();
+                        eprintln!("Seed: {}",game.seed());
                     }
                 }
             }
         }
-        window.update_with_buffer(&buffer.0.as_ref(),1000,1000).unwrap();
+        if let Some(screen)=&controls {
+            screen.draw(&mut buffer,&settings.keys);
+        }
+        window.update_with_buffer(buffer.0.as_ref(),1000,1000).unwrap();
     }
 }
@@ -0,0 +1,152 @@
+//! Rigid body response between asteroids. Asteroids are treated as discs with mass proportional
+//! to their area. Friction at the contact point turns glancing hits into spin.
+
+
+use crate::{
+    collision::wrapped_offset,
+    grid::Grid,
+    Asteroid,
+    Vec2,
+};
+
+
+/// Passes over all contacts per tick. Resolving one contact can push an asteroid into another, so
+/// clusters need a few passes to settle.
+const ITERATIONS:usize=4;
+
+
+/// Separates overlapping asteroids in a world of `size` and exchanges momentum between them.
+/// `grid` is only used as scratch space and is left holding the asteroids from the last pass.
+pub fn resolve_asteroid_collisions(asteroids:&mut [Asteroid],size:Vec2,restitution:f32,friction:f32,grid:&mut Grid) {
+    let mut nearby=Vec::new();
+    for _ in 0..ITERATIONS {
+        grid.clear();
+        for (i,asteroid) in asteroids.iter().enumerate() {
+            grid.insert(i,asteroid.pos,asteroid.radius);
+        }
+        let mut any=false;
+        for a in 0..asteroids.len() {
+            grid.query(asteroids[a].pos,asteroids[a].radius,&mut nearby);
+            // Each pair is only resolved once, from its lower index
+            for &b in nearby.iter().filter(|b|**b>a) {
+                let (left,right)=asteroids.split_at_mut(b);
+                any|=resolve_pair(&mut left[a],&mut right[0],size,restitution,friction);
+            }
+        }
+        if !any {break}
+    }
+}
+
+/// Resolves one contact and returns true if the asteroids were touching
+fn resolve_pair(a:&mut Asteroid,b:&mut Asteroid,size:Vec2,restitution:f32,friction:f32)->bool {
+    let offset=wrapped_offset(a.pos,b.pos,size);
+    let dist_sq=offset.magnitude_squared();
+    let min_dist=a.radius+b.radius;
+    if dist_sq>min_dist*min_dist {return false}
+    let dist=dist_sq.sqrt();
+    let normal=if dist>0.0 {offset/dist} else {Vec2::unit_x()};
+    let inv_a=1.0/a.mass();
+    let inv_b=1.0/b.mass();
+    let inv_sum=inv_a+inv_b;
+    // Push them apart along the normal, the lighter one moving further
+    let depth=min_dist-dist;
+    a.pos-=normal*(depth*inv_a/inv_sum);
+    b.pos+=normal*(depth*inv_b/inv_sum);
+    // Only exchange momentum if they are still moving into each other
+    let closing=(b.vel-a.vel).dot(normal);
+    if closing<0.0 {
+        let impulse=-(1.0+restitution)*closing/inv_sum;
+        a.vel-=normal*(impulse*inv_a);
+        b.vel+=normal*(impulse*inv_b);
+        // Friction works against the sliding of the surfaces at the contact point, which
+        // includes their spin. It can't be stronger than the normal impulse allows.
+        let tangent=Vec2::new(-normal.y,normal.x);
+        let sliding=(b.vel-a.vel).dot(tangent)-b.angular_vel*b.radius-a.angular_vel*a.radius;
+        let inv_ia=a.radius*a.radius/a.inertia();
+        let inv_ib=b.radius*b.radius/b.inertia();
+        let max=friction*impulse;
+        let tangent_impulse=(-sliding/(inv_sum+inv_ia+inv_ib)).clamp(-max,max);
+        a.vel-=tangent*(tangent_impulse*inv_a);
+        b.vel+=tangent*(tangent_impulse*inv_b);
+        a.angular_vel-=tangent_impulse*a.radius/a.inertia();
+        b.angular_vel-=tangent_impulse*b.radius/b.inertia();
+    }
+    return true;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE:Vec2=Vec2::new(1000.0,1000.0);
+
+    fn asteroid(x:f32,y:f32,vel_x:f32,vel_y:f32,radius:f32)->Asteroid {
+        let pos=Vec2::new(x,y);
+        Asteroid {
+            pos,
+            prev_pos:pos,
+            vel:Vec2::new(vel_x,vel_y),
+            radius,
+            model_index:0,
+            angle:0.0,
+            prev_angle:0.0,
+            angular_vel:0.0,
+        }
+    }
+
+    fn momentum(asteroids:&[Asteroid])->Vec2 {
+        asteroids.iter().map(|a|a.vel*a.mass()).sum()
+    }
+
+    fn energy(asteroids:&[Asteroid])->f32 {
+        asteroids.iter().map(|a|a.mass()*a.vel.magnitude_squared()/2.0).sum()
+    }
+
+    /// Resolves `asteroids` elastically without friction and checks nothing was gained or lost
+    fn assert_conserved(mut asteroids:Vec<Asteroid>) {
+        let before=(momentum(&asteroids),energy(&asteroids));
+        let velocities=asteroids.iter().map(|a|a.vel).collect::<Vec<_>>();
+        resolve_asteroid_collisions(&mut asteroids,SIZE,1.0,0.0,&mut Grid::new(SIZE,100.0));
+        assert!(asteroids.iter().zip(velocities).any(|(a,vel)|a.vel!=vel));
+        let after=(momentum(&asteroids),energy(&asteroids));
+        assert!((after.0-before.0).magnitude()<=before.0.magnitude()*1e-4+1e-2);
+        assert!((after.1-before.1).abs()<=before.1*1e-4);
+    }
+
+    #[test]
+    fn elastic_pairs_conserve_momentum_and_energy() {
+        assert_conserved(vec![
+            asteroid(100.0,100.0,50.0,0.0,20.0),
+            asteroid(135.0,105.0,-30.0,10.0,30.0),
+        ]);
+    }
+
+    #[test]
+    fn elastic_clusters_conserve_momentum_and_energy() {
+        assert_conserved(vec![
+            asteroid(500.0,500.0,40.0,-20.0,25.0),
+            asteroid(540.0,510.0,-60.0,5.0,20.0),
+            asteroid(515.0,540.0,10.0,-70.0,30.0),
+        ]);
+    }
+
+    #[test]
+    fn contacts_across_the_edge_are_resolved() {
+        assert_conserved(vec![
+            asteroid(990.0,500.0,50.0,0.0,20.0),
+            asteroid(15.0,500.0,-50.0,0.0,20.0),
+        ]);
+    }
+
+    #[test]
+    fn inelastic_contacts_stop_closing() {
+        let mut asteroids=vec![
+            asteroid(100.0,100.0,50.0,0.0,20.0),
+            asteroid(135.0,105.0,-30.0,10.0,30.0),
+        ];
+        let normal=(asteroids[1].pos-asteroids[0].pos).normalized();
+        resolve_asteroid_collisions(&mut asteroids,SIZE,0.0,0.0,&mut Grid::new(SIZE,100.0));
+        assert!((asteroids[1].vel-asteroids[0].vel).dot(normal).abs()<1e-3);
+    }
+}
@@ -0,0 +1,133 @@
+//! Pickups that sometimes drop out of destroyed asteroids. Most of them give the player a timed
+//! effect, the extra life is used up straight away.
+
+
+use crate::Vec2;
+
+
+/// Seconds a power-up drifts around before it disappears if nobody picks it up
+pub const POWERUP_LIFETIME:f32=10.0;
+/// Seconds before disappearing that a power-up starts blinking
+pub const POWERUP_WARN_TIME:f32=2.0;
+/// Pickup radius around the center of a power-up
+pub const POWERUP_RADIUS:f32=12.0;
+/// Longest shot delay while rapid fire is active. A shorter `Config::shot_delay` is kept.
+pub const RAPID_FIRE_DELAY:f32=0.05;
+/// Angle between the bullets of a spread shot, in degrees
+pub const SPREAD_ANGLE:f32=12.0;
+/// Box drawn around every power-up icon, in screen space
+pub const POWERUP_FRAME:&[Vec2]=&[
+    Vec2::new(-0.012,-0.012),
+    Vec2::new(0.012,-0.012),
+    Vec2::new(0.012,-0.012),
+    Vec2::new(0.012,0.012),
+    Vec2::new(0.012,0.012),
+    Vec2::new(-0.012,0.012),
+    Vec2::new(-0.012,0.012),
+    Vec2::new(-0.012,-0.012),
+];
+/// Three lines fanning out
+const SPREAD_ICON:&[Vec2]=&[
+    Vec2::new(0.0,-0.008),
+    Vec2::new(0.0,0.008),
+    Vec2::new(0.0,-0.008),
+    Vec2::new(-0.007,0.008),
+    Vec2::new(0.0,-0.008),
+    Vec2::new(0.007,0.008),
+];
+/// Two chevrons pointing up
+const RAPID_FIRE_ICON:&[Vec2]=&[
+    Vec2::new(-0.006,-0.002),
+    Vec2::new(0.0,0.004),
+    Vec2::new(0.0,0.004),
+    Vec2::new(0.006,-0.002),
+    Vec2::new(-0.006,-0.008),
+    Vec2::new(0.0,-0.002),
+    Vec2::new(0.0,-0.002),
+    Vec2::new(0.006,-0.008),
+];
+/// An arrow through a wall
+const PIERCING_ICON:&[Vec2]=&[
+    Vec2::new(0.0,-0.008),
+    Vec2::new(0.0,0.008),
+    Vec2::new(0.0,0.008),
+    Vec2::new(-0.004,0.004),
+    Vec2::new(0.0,0.008),
+    Vec2::new(0.004,0.004),
+    Vec2::new(-0.007,0.0),
+    Vec2::new(0.007,0.0),
+];
+/// A diamond bubble
+const SHIELD_ICON:&[Vec2]=&[
+    Vec2::new(0.0,0.008),
+    Vec2::new(0.008,0.0),
+    Vec2::new(0.008,0.0),
+    Vec2::new(0.0,-0.008),
+    Vec2::new(0.0,-0.008),
+    Vec2::new(-0.008,0.0),
+    Vec2::new(-0.008,0.0),
+    Vec2::new(0.0,0.008),
+];
+/// A plus sign
+const EXTRA_LIFE_ICON:&[Vec2]=&[
+    Vec2::new(0.0,-0.008),
+    Vec2::new(0.0,0.008),
+    Vec2::new(-0.008,0.0),
+    Vec2::new(0.008,0.0),
+];
+
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum PowerUpKind {
+    /// Fires three bullets in a fan
+    Spread,
+    /// Fires at least every `RAPID_FIRE_DELAY`, or at `Config::shot_delay` if that's faster
+    RapidFire,
+    /// Bullets go through asteroids instead of stopping at the first one
+    Piercing,
+    /// Holds the shield up without using any energy
+    Shield,
+    ExtraLife,
+}
+impl PowerUpKind {
+    pub const ALL:[PowerUpKind;5]=[
+        PowerUpKind::Spread,
+        PowerUpKind::RapidFire,
+        PowerUpKind::Piercing,
+        PowerUpKind::Shield,
+        PowerUpKind::ExtraLife,
+    ];
+    /// Symbol drawn inside `POWERUP_FRAME`, in screen space
+    pub fn icon(self)->&'static [Vec2] {
+        match self {
+            PowerUpKind::Spread=>SPREAD_ICON,
+            PowerUpKind::RapidFire=>RAPID_FIRE_ICON,
+            PowerUpKind::Piercing=>PIERCING_ICON,
+            PowerUpKind::Shield=>SHIELD_ICON,
+            PowerUpKind::ExtraLife=>EXTRA_LIFE_ICON,
+        }
+    }
+    pub fn name(self)->&'static str {
+        match self {
+            PowerUpKind::Spread=>"Spread",
+            PowerUpKind::RapidFire=>"Rapid",
+            PowerUpKind::Piercing=>"Pierce",
+            PowerUpKind::Shield=>"Shield",
+            PowerUpKind::ExtraLife=>"Life",
+        }
+    }
+    /// Whether the effect lasts for a while instead of being used up on pickup
+    pub fn is_timed(self)->bool {
+        self!=PowerUpKind::ExtraLife
+    }
+}
+
+
+pub struct PowerUp {
+    pub kind:PowerUpKind,
+    pub pos:Vec2,
+    pub prev_pos:Vec2,
+    pub vel:Vec2,
+    /// Game time the power-up disappears at if it isn't picked up
+    pub expires_at:f32,
+}
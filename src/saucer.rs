@@ -0,0 +1,138 @@
+//! Enemy saucers. A large one fires in random directions, a small one leads its shots at where
+//! the player is going to be.
+
+
+use crate::Vec2;
+
+
+/// Speed of saucer bullets. They don't inherit the saucer's velocity so the small saucer's aim
+/// stays exact.
+pub const SAUCER_BULLET_VEL:f32=400.0;
+/// Seconds a saucer bullet flies before it disappears
+pub const SAUCER_BULLET_LIFETIME:f32=1.5;
+/// Seconds between saucer changes of vertical direction
+pub const SAUCER_TURN_TIME:f32=1.0;
+/// Outline of the saucer in screen space, before the size of the kind is applied
+pub const SAUCER_VECTOR:&[Vec2]=&[
+    // dome
+    Vec2::new(-0.008,0.012),
+    Vec2::new(0.008,0.012),
+    Vec2::new(-0.008,0.012),
+    Vec2::new(-0.012,0.004),
+    Vec2::new(0.008,0.012),
+    Vec2::new(0.012,0.004),
+    // rim
+    Vec2::new(-0.012,0.004),
+    Vec2::new(0.012,0.004),
+    Vec2::new(-0.012,0.004),
+    Vec2::new(-0.024,-0.004),
+    Vec2::new(0.012,0.004),
+    Vec2::new(0.024,-0.004),
+    Vec2::new(-0.024,-0.004),
+    Vec2::new(0.024,-0.004),
+    // hull
+    Vec2::new(-0.024,-0.004),
+    Vec2::new(-0.012,-0.012),
+    Vec2::new(0.024,-0.004),
+    Vec2::new(0.012,-0.012),
+    Vec2::new(-0.012,-0.012),
+    Vec2::new(0.012,-0.012),
+];
+
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum SaucerKind {
+    Large,
+    Small,
+}
+impl SaucerKind {
+    /// Size relative to `SAUCER_VECTOR`
+    pub fn scale(self)->f32 {
+        match self {
+            SaucerKind::Large=>1.5,
+            SaucerKind::Small=>0.75,
+        }
+    }
+    /// Horizontal speed
+    pub fn speed(self)->f32 {
+        match self {
+            SaucerKind::Large=>100.0,
+            SaucerKind::Small=>150.0,
+        }
+    }
+    /// Seconds between shots
+    pub fn fire_delay(self)->f32 {
+        match self {
+            SaucerKind::Large=>1.2,
+            SaucerKind::Small=>0.9,
+        }
+    }
+}
+
+
+pub struct Saucer {
+    pub kind:SaucerKind,
+    pub pos:Vec2,
+    pub prev_pos:Vec2,
+    pub vel:Vec2,
+    /// Horizontal distance left before it leaves the screen
+    pub range:f32,
+    /// Game time of the next shot
+    pub next_shot:f32,
+    /// Game time of the next change of vertical direction
+    pub next_turn:f32,
+}
+
+
+/// Direction to fire a bullet of `speed` to hit a target at `offset` that keeps moving at `vel`.
+/// Aims straight at the target if the bullet can't catch up with it.
+pub fn lead_target(offset:Vec2,vel:Vec2,speed:f32)->Vec2 {
+    // Solve |offset+vel*t|=speed*t for the earliest positive time
+    let a=vel.dot(vel)-speed*speed;
+    let b=2.0*offset.dot(vel);
+    let c=offset.dot(offset);
+    let t=if a.abs()<f32::EPSILON {
+        if b<0.0 {-c/b} else {-1.0}
+    } else {
+        let disc=b*b-4.0*a*c;
+        if disc<0.0 {
+            -1.0
+        } else {
+            let root=disc.sqrt();
+            let t0=(-b-root)/(2.0*a);
+            let t1=(-b+root)/(2.0*a);
+            if t0>0.0&&t1>0.0 {t0.min(t1)} else {t0.max(t1)}
+        }
+    };
+    let aim=if t>0.0 {offset+vel*t} else {offset};
+    return aim.normalized();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn still_targets_are_aimed_at_directly() {
+        let dir=lead_target(Vec2::new(300.0,400.0),Vec2::zero(),100.0);
+        assert!((dir-Vec2::new(0.6,0.8)).magnitude()<1e-5);
+    }
+
+    #[test]
+    fn moving_targets_are_hit() {
+        let offset=Vec2::new(500.0,0.0);
+        let vel=Vec2::new(0.0,100.0);
+        let speed=300.0;
+        let dir=lead_target(offset,vel,speed);
+        // The bullet and the target meet when the bullet has covered the distance to the target
+        let t=offset.magnitude()/(speed*speed-vel.magnitude_squared()).sqrt();
+        assert!((dir*speed*t-(offset+vel*t)).magnitude()<1e-2);
+    }
+
+    #[test]
+    fn targets_too_fast_to_catch_are_aimed_at_directly() {
+        let dir=lead_target(Vec2::new(100.0,0.0),Vec2::new(500.0,0.0),100.0);
+        assert_eq!(dir,Vec2::unit_x());
+    }
+}
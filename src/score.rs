@@ -0,0 +1,72 @@
+//! Points awarded for destroying things, by asteroid size class and saucer kind.
+
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use crate::{
+    saucer::SaucerKind,
+    Config,
+};
+
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum SizeClass {
+    /// As big as a freshly spawned asteroid
+    Large,
+    /// A piece that still splits when shot
+    Medium,
+    /// A piece too small to split
+    Small,
+}
+impl SizeClass {
+    /// Class of an asteroid of `radius` under the size limits in `config`
+    pub fn of(radius:f32,config:&Config)->SizeClass {
+        if radius>=config.new_asteroid_min_radius {
+            SizeClass::Large
+        } else if radius>config.asteroid_min_radius {
+            SizeClass::Medium
+        } else {
+            SizeClass::Small
+        }
+    }
+}
+
+
+/// Points for each kind of target
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(default,deny_unknown_fields)]
+pub struct ScoreTable {
+    pub large_asteroid:u64,
+    pub medium_asteroid:u64,
+    pub small_asteroid:u64,
+    pub large_saucer:u64,
+    pub small_saucer:u64,
+}
+impl ScoreTable {
+    pub fn asteroid(&self,class:SizeClass)->u64 {
+        match class {
+            SizeClass::Large=>self.large_asteroid,
+            SizeClass::Medium=>self.medium_asteroid,
+            SizeClass::Small=>self.small_asteroid,
+        }
+    }
+    pub fn saucer(&self,kind:SaucerKind)->u64 {
+        match kind {
+            SaucerKind::Large=>self.large_saucer,
+            SaucerKind::Small=>self.small_saucer,
+        }
+    }
+}
+impl Default for ScoreTable {
+    fn default()->ScoreTable {
+        ScoreTable {
+            large_asteroid:20,
+            medium_asteroid:50,
+            small_asteroid:100,
+            large_saucer:200,
+            small_saucer:1000,
+        }
+    }
+}
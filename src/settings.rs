@@ -0,0 +1,365 @@
+//! The settings file: render rate, key bindings and the game config, all optional. Anything left
+//! out keeps its default.
+
+
+use std::{
+    env,
+    fs::{
+        create_dir_all,
+        read_to_string,
+        write,
+    },
+    io::ErrorKind,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+use minifb::Key;
+use asteroids::Buttons;
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use toml::{
+    value::Table,
+    Value,
+};
+
+
+/// Settings file in the working directory, read instead of the user's one when it exists
+pub const LOCAL_PATH:&str="asteroids.toml";
+/// Opens and closes the controls screen, so it can't be bound to an action
+pub const RESERVED_KEY:Key=Key::Escape;
+/// Every key that can be bound, named as in the settings file
+const KEYS:&[Key]=&[
+    Key::Key0,Key::Key1,Key::Key2,Key::Key3,Key::Key4,Key::Key5,Key::Key6,Key::Key7,Key::Key8,
+    Key::Key9,Key::A,Key::B,Key::C,Key::D,Key::E,Key::F,Key::G,Key::H,Key::I,Key::J,Key::K,
+    Key::L,Key::M,Key::N,Key::O,Key::P,Key::Q,Key::R,Key::S,Key::T,Key::U,Key::V,Key::W,
+    Key::X,Key::Y,Key::Z,Key::F1,Key::F2,Key::F3,Key::F4,Key::F5,Key::F6,Key::F7,Key::F8,
+    Key::F9,Key::F10,Key::F11,Key::F12,Key::F13,Key::F14,Key::F15,Key::Down,Key::Left,
+    Key::Right,Key::Up,Key::Apostrophe,Key::Backquote,Key::Backslash,Key::Comma,Key::Equal,
+    Key::LeftBracket,Key::Minus,Key::Period,Key::RightBracket,Key::Semicolon,Key::Slash,
+    Key::Backspace,Key::Delete,Key::End,Key::Enter,Key::Escape,Key::Home,Key::Insert,Key::Menu,
+    Key::PageDown,Key::PageUp,Key::Pause,Key::Space,Key::Tab,Key::NumLock,Key::CapsLock,
+    Key::ScrollLock,Key::LeftShift,Key::RightShift,Key::LeftCtrl,Key::RightCtrl,Key::NumPad0,
+    Key::NumPad1,Key::NumPad2,Key::NumPad3,Key::NumPad4,Key::NumPad5,Key::NumPad6,Key::NumPad7,
+    Key::NumPad8,Key::NumPad9,Key::NumPadDot,Key::NumPadSlash,Key::NumPadAsterisk,Key::NumPadMinus,
+    Key::NumPadPlus,Key::NumPadEnter,Key::LeftAlt,Key::RightAlt,Key::LeftSuper,Key::RightSuper,
+];
+
+
+/// Something the player can do with a key
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Action {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Fire,
+    Hyperspace,
+    Shield,
+}
+impl Action {
+    pub const ALL:[Action;7]=[
+        Action::Forward,
+        Action::Backward,
+        Action::Left,
+        Action::Right,
+        Action::Fire,
+        Action::Hyperspace,
+        Action::Shield,
+    ];
+    /// Name of the action in the settings file
+    pub fn name(self)->&'static str {
+        match self {
+            Action::Forward=>"forward",
+            Action::Backward=>"backward",
+            Action::Left=>"left",
+            Action::Right=>"right",
+            Action::Fire=>"fire",
+            Action::Hyperspace=>"hyperspace",
+            Action::Shield=>"shield",
+        }
+    }
+}
+
+
+/// The keys bound to each action. Any of an action's keys triggers it.
+#[derive(Clone,Serialize,Deserialize)]
+#[serde(default,deny_unknown_fields)]
+pub struct KeyMap {
+    #[serde(with="key_names")]
+    pub forward:Vec<Key>,
+    #[serde(with="key_names")]
+    pub backward:Vec<Key>,
+    #[serde(with="key_names")]
+    pub left:Vec<Key>,
+    #[serde(with="key_names")]
+    pub right:Vec<Key>,
+    #[serde(with="key_names")]
+    pub fire:Vec<Key>,
+    #[serde(with="key_names")]
+    pub hyperspace:Vec<Key>,
+    #[serde(with="key_names")]
+    pub shield:Vec<Key>,
+}
+impl KeyMap {
+    pub fn keys(&self,action:Action)->&Vec<Key> {
+        match action {
+            Action::Forward=>&self.forward,
+            Action::Backward=>&self.backward,
+            Action::Left=>&self.left,
+            Action::Right=>&self.right,
+            Action::Fire=>&self.fire,
+            Action::Hyperspace=>&self.hyperspace,
+            Action::Shield=>&self.shield,
+        }
+    }
+    pub fn keys_mut(&mut self,action:Action)->&mut Vec<Key> {
+        match action {
+            Action::Forward=>&mut self.forward,
+            Action::Backward=>&mut self.backward,
+            Action::Left=>&mut self.left,
+            Action::Right=>&mut self.right,
+            Action::Fire=>&mut self.fire,
+            Action::Hyperspace=>&mut self.hyperspace,
+            Action::Shield=>&mut self.shield,
+        }
+    }
+    /// The action `key` is bound to, if any
+    pub fn action_of(&self,key:Key)->Option<Action> {
+        Action::ALL.iter()
+            .copied()
+            .find(|action|self.keys(*action).contains(&key))
+    }
+    /// Checks that every action has a key, no key does two things and nothing uses `RESERVED_KEY`
+    pub fn validate(&self)->Result<(),String> {
+        for &action in Action::ALL.iter() {
+            let keys=self.keys(action);
+            if keys.is_empty() {
+                return Err(format!("`{}` has no keys",action.name()));
+            }
+            for (i,&key) in keys.iter().enumerate() {
+                if key==RESERVED_KEY {
+                    return Err(format!("`{}` is reserved for the controls screen",key_name(key)));
+                }
+                if keys[..i].contains(&key) {
+                    return Err(format!("`{}` is bound to `{}` twice",key_name(key),action.name()));
+                }
+                match self.action_of(key) {
+                    Some(other) if other!=action=>return Err(format!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        key_name(key),
+                        other.name(),
+                        action.name(),
+                    )),
+                    _=>{},
+                }
+            }
+        }
+        return Ok(());
+    }
+    /// Which buttons are held according to `is_down`
+    pub fn buttons(&self,is_down:impl Fn(Key)->bool)->Buttons {
+        let held=|action|self.keys(action).iter().any(|key|is_down(*key));
+        Buttons {
+            forward:held(Action::Forward),
+            backward:held(Action::Backward),
+            left:held(Action::Left),
+            right:held(Action::Right),
+            fire:held(Action::Fire),
+            hyperspace:held(Action::Hyperspace),
+            shield:held(Action::Shield),
+        }
+    }
+}
+impl Default for KeyMap {
+    fn default()->KeyMap {
+        KeyMap {
+            forward:vec![Key::W,Key::Up],
+            backward:vec![Key::S,Key::Down],
+            left:vec![Key::A,Key::Left],
+            right:vec![Key::D,Key::Right],
+            fire:vec![Key::F,Key::Space],
+            hyperspace:vec![Key::Q],
+            shield:vec![Key::E],
+        }
+    }
+}
+
+
+/// Contents of the settings file
+#[derive(Serialize,Deserialize)]
+#[serde(default,deny_unknown_fields)]
+pub struct SettingsFile {
+    /// Render rate. The simulation always steps at `TICK_RATE` no matter what this is.
+    pub fps:usize,
+    pub keys:KeyMap,
+    /// Overrides for the game `Config`, plus an optional `mode` to start from
+    pub game:Table,
+}
+impl Default for SettingsFile {
+    fn default()->SettingsFile {
+        SettingsFile {
+            fps:30,
+            keys:KeyMap::default(),
+            game:Table::new(),
+        }
+    }
+}
+impl SettingsFile {
+    /// Reads the settings file at `path`. If `required` is false a missing file gives the defaults.
+    pub fn load(path:&Path,required:bool)->Result<SettingsFile,String> {
+        let source=match read_to_string(path) {
+            Ok(source)=>source,
+            Err(e) if e.kind()==ErrorKind::NotFound&&!required=>return Ok(SettingsFile::default()),
+            Err(e)=>return Err(format!("Could not read `{}`: {}",path.display(),e)),
+        };
+        let settings:SettingsFile=toml::from_str(&source).map_err(|e|format!("{}: {}",path.display(),e))?;
+        if settings.fps==0 {
+            return Err(format!("{}: `fps` must be at least 1",path.display()));
+        }
+        settings.keys.validate().map_err(|e|format!("{}: keys: {}",path.display(),e))?;
+        return Ok(settings);
+    }
+}
+
+
+/// Settings file used when `--config` isn't given: `LOCAL_PATH` if it exists, otherwise
+/// `asteroids.toml` in the user's config directory
+pub fn default_path()->PathBuf {
+    let local=PathBuf::from(LOCAL_PATH);
+    if local.exists() {
+        return local;
+    }
+    return match user_dir("XDG_CONFIG_HOME",".config") {
+        Some(dir)=>dir.join(LOCAL_PATH),
+        None=>local,
+    };
+}
+
+/// This game's directory under the XDG base directory in the environment variable `var`, or under
+/// `fallback` in the home directory if it isn't set
+pub fn user_dir(var:&str,fallback:&str)->Option<PathBuf> {
+    let base=env::var_os(var)
+        .filter(|dir|!dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(||env::var_os("HOME").map(|home|Path::new(&home).join(fallback)));
+    return base.map(|base|base.join("asteroids"));
+}
+
+/// Writes `keys` into the settings file at `path`, keeping everything else in it
+pub fn save_keys(path:&Path,keys:&KeyMap)->Result<(),String> {
+    let mut file=match read_to_string(path) {
+        Ok(source)=>source.parse::<Value>().map_err(|e|format!("{}: {}",path.display(),e))?,
+        Err(e) if e.kind()==ErrorKind::NotFound=>Value::Table(Table::new()),
+        Err(e)=>return Err(format!("Could not read `{}`: {}",path.display(),e)),
+    };
+    let keys=Value::try_from(keys).map_err(|e|e.to_string())?;
+    match &mut file {
+        Value::Table(table)=>{table.insert("keys".to_string(),keys);},
+        _=>return Err(format!("{}: not a table",path.display())),
+    }
+    if let Some(dir)=path.parent().filter(|dir|!dir.as_os_str().is_empty()) {
+        create_dir_all(dir).map_err(|e|format!("Could not create `{}`: {}",dir.display(),e))?;
+    }
+    let source=toml::to_string(&file).map_err(|e|e.to_string())?;
+    return write(path,source).map_err(|e|format!("Could not write `{}`: {}",path.display(),e));
+}
+
+
+/// Looks up a key by its name, ignoring case
+pub fn key_from_name(name:&str)->Option<Key> {
+    KEYS.iter()
+        .copied()
+        .find(|key|key_name(*key).eq_ignore_ascii_case(name))
+}
+
+/// Name of a key in the settings file. The number keys are `Key0` to `Key9`.
+pub fn key_name(key:Key)->String {
+    format!("{:?}",key)
+}
+
+
+/// Reads and writes lists of `Key`s by name. A single name is read as a list of one.
+mod key_names {
+    use minifb::Key;
+    use serde::{
+        de::Error,
+        ser::SerializeSeq,
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Names {
+        One(String),
+        Many(Vec<String>),
+    }
+    pub fn serialize<S:Serializer>(keys:&[Key],serializer:S)->Result<S::Ok,S::Error> {
+        let mut seq=serializer.serialize_seq(Some(keys.len()))?;
+        for key in keys.iter() {
+            seq.serialize_element(&super::key_name(*key))?;
+        }
+        seq.end()
+    }
+    pub fn deserialize<'de,D:Deserializer<'de>>(deserializer:D)->Result<Vec<Key>,D::Error> {
+        let names=match Names::deserialize(deserializer)? {
+            Names::One(name)=>vec![name],
+            Names::Many(names)=>names,
+        };
+        names.iter()
+            .map(|name|super::key_from_name(name).ok_or_else(||D::Error::custom(format!("unknown key `{}`",name))))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_keys_are_valid() {
+        assert!(KeyMap::default().validate().is_ok());
+    }
+
+    #[test]
+    fn keys_bound_to_two_actions_are_rejected() {
+        let mut keys=KeyMap::default();
+        keys.fire.push(Key::W);
+        assert_eq!(keys.validate().err().unwrap(),"`W` is bound to both `forward` and `fire`");
+    }
+
+    #[test]
+    fn keys_bound_twice_to_one_action_are_rejected() {
+        let mut keys=KeyMap::default();
+        keys.shield.push(Key::E);
+        assert_eq!(keys.validate().err().unwrap(),"`E` is bound to `shield` twice");
+    }
+
+    #[test]
+    fn the_reserved_key_is_rejected() {
+        let mut keys=KeyMap::default();
+        keys.hyperspace.push(RESERVED_KEY);
+        assert_eq!(keys.validate().err().unwrap(),"`Escape` is reserved for the controls screen");
+    }
+
+    #[test]
+    fn actions_without_keys_are_rejected() {
+        let mut keys=KeyMap::default();
+        keys.left.clear();
+        assert_eq!(keys.validate().err().unwrap(),"`left` has no keys");
+    }
+
+    #[test]
+    fn single_key_names_are_read_as_lists() {
+        let keys:KeyMap=toml::from_str("fire = \"space\"\nleft = [\"A\", \"J\"]").unwrap();
+        assert_eq!(keys.fire,vec![Key::Space]);
+        assert_eq!(keys.left,vec![Key::A,Key::J]);
+        assert_eq!(keys.forward,KeyMap::default().forward);
+        assert!(toml::from_str::<KeyMap>("fire = \"Nope\"").is_err());
+    }
+}
@@ -1,69 +1,598 @@
+//! Imports the outlines of SVG paths as line lists. Curves and arcs are flattened into lines, and
+//! group and path transforms and the `viewBox` are applied.
+
+
+use std::f32::consts::{
+    FRAC_PI_2,
+    TAU,
+};
 use svg::{
-    node::element::{
-        path::{
-            Position,
-            Command,
-            Data,
+    node::{
+        element::{
+            path::{
+                Position,
+                Command,
+                Data,
+            },
+            tag::Type,
         },
+        Attributes,
     },
     parser::Event,
 };
 use crate::Vec2;
 
 
-pub fn svg_to_vector(source:&str)->Option<Vec<Vec2>> {
-    if let Ok(events)=svg::read(source) {
-        let mut vector=Vec::new();
-        let mut cursor=Vec2::zero();
-        let mut first=None;
-        for event in events {
-            match event {
-                Event::Tag(_,_,attributes)=>{
-                    if let Some(data)=attributes.get("d") {
-                        if let Ok(data)=Data::parse(data) {
-                            for command in data.iter() {
-                                match &command {
-                                    &Command::Move(pos,params)=>{
-                                        match pos {
-                                            Position::Absolute=>{
-                                                cursor=Vec2::new(params[0],params[1]);
-                                            },
-                                            Position::Relative=>{
-                                                cursor+=Vec2::new(params[0],params[1]);
-                                            },
-                                        }
-                                    },
-                                    &Command::Line(pos,params)=>{
-                                        if let None=first {
-                                            first=Some(cursor);
-                                        }
-                                        vector.push(cursor);
-                                        match pos {
-                                            Position::Absolute=>{
-                                                cursor=Vec2::new(params[0],params[1]);
-                                            },
-                                            Position::Relative=>{
-                                                cursor+=Vec2::new(params[0],params[1]);
-                                            },
-                                        }
-                                        vector.push(cursor);
-                                    },
-                                    _=>{},
-                                }
-                            }
-                        }
+/// Most lines a single curve or arc is flattened into, however small the tolerance
+pub const MAX_CURVE_SEGMENTS:usize=64;
+
+
+/// Reads every path in an SVG file into one line list, centered on its centroid and scaled so the
+/// furthest point is 1 away. Curves are flattened so no point on them is further than `tolerance`
+/// from the lines, measured in pixels at the file's own size. Fails with the reason if the file
+/// isn't valid SVG or has nothing to draw.
+pub fn svg_to_vector(source:&str,tolerance:f32)->Result<Vec<Vec2>,String> {
+    let events=svg::read(source).map_err(|e|e.to_string())?;
+    let mut vector=Vec::new();
+    // Transform of every open element, from its own coordinates to the file's
+    let mut stack=vec![Transform::IDENTITY];
+    for event in events {
+        if let Event::Error(e)=event {
+            return Err(e.to_string());
+        }
+        if let Event::Tag(name,kind,attributes)=event {
+            if kind==Type::End {
+                stack.pop();
+                continue;
+            }
+            let mut transform=*stack.last().unwrap_or(&Transform::IDENTITY);
+            if name=="svg" {
+                transform=transform.then(Transform::view_box(&attributes));
+            }
+            if let Some(list)=attributes.get("transform") {
+                transform=transform.then(Transform::parse(list));
+            }
+            if name=="path" {
+                if let Some(data)=attributes.get("d") {
+                    let data=Data::parse(data).map_err(|e|format!("bad path data: {}",e))?;
+                    let mut path=PathFlattener::new(&mut vector,tolerance,transform);
+                    for command in data.iter() {
+                        path.command(command);
                     }
+                    path.close();
                 }
-                _=>{},
             }
+            if kind==Type::Start {
+                stack.push(transform);
+            }
+        }
+    }
+    normalize(&mut vector).ok_or("no lines to draw")?;
+    return Ok(vector);
+}
+
+/// Moves the centroid of a line list's segments to the origin and scales it to a radius of 1.
+/// `None` if it has no length.
+pub fn normalize(lines:&mut [Vec2])->Option<()> {
+    let mut length=0.0;
+    let mut centroid=Vec2::zero();
+    for segment in lines.chunks_exact(2) {
+        let segment_length=segment[0].distance(segment[1]);
+        length+=segment_length;
+        centroid+=(segment[0]+segment[1])/2.0*segment_length;
+    }
+    if length<=0.0 {
+        return None;
+    }
+    centroid/=length;
+    let radius=lines.iter()
+        .map(|vtx|vtx.distance(centroid))
+        .fold(0.0,f32::max);
+    for vtx in lines.iter_mut() {
+        *vtx=(*vtx-centroid)/radius;
+    }
+    return Some(());
+}
+
+
+/// A 2D affine transform in SVG's `matrix(a b c d e f)` form
+#[derive(Clone,Copy)]
+struct Transform([f32;6]);
+impl Transform {
+    const IDENTITY:Transform=Transform([1.0,0.0,0.0,1.0,0.0,0.0]);
+    fn apply(&self,p:Vec2)->Vec2 {
+        let [a,b,c,d,e,f]=self.0;
+        Vec2::new(a*p.x+c*p.y+e,b*p.x+d*p.y+f)
+    }
+    /// `other` followed by this, the way nested SVG transforms combine
+    fn then(&self,other:Transform)->Transform {
+        let [a,b,c,d,e,f]=self.0;
+        let [oa,ob,oc,od,oe,of]=other.0;
+        Transform([
+            a*oa+c*ob,
+            b*oa+d*ob,
+            a*oc+c*od,
+            b*oc+d*od,
+            a*oe+c*of+e,
+            b*oe+d*of+f,
+        ])
+    }
+    /// Largest factor this stretches lengths by
+    fn scale(&self)->f32 {
+        let [a,b,c,d,_,_]=self.0;
+        a.hypot(b).max(c.hypot(d))
+    }
+    /// Parses a `transform` attribute. Anything it can't read is left out.
+    fn parse(list:&str)->Transform {
+        let mut transform=Transform::IDENTITY;
+        for item in list.split(')') {
+            let (name,args)=match item.split_once('(') {
+                Some(item)=>item,
+                None=>continue,
+            };
+            let args=args.split(|c:char|c==','||c.is_whitespace())
+                .filter(|arg|!arg.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<f32>,_>>();
+            let args=match args {
+                Ok(args)=>args,
+                Err(_)=>continue,
+            };
+            let item=match (name.trim_matches(|c:char|c==','||c.is_whitespace()),args.as_slice()) {
+                ("matrix",&[a,b,c,d,e,f])=>Transform([a,b,c,d,e,f]),
+                ("translate",&[x])=>Transform([1.0,0.0,0.0,1.0,x,0.0]),
+                ("translate",&[x,y])=>Transform([1.0,0.0,0.0,1.0,x,y]),
+                ("scale",&[s])=>Transform([s,0.0,0.0,s,0.0,0.0]),
+                ("scale",&[x,y])=>Transform([x,0.0,0.0,y,0.0,0.0]),
+                ("rotate",&[angle])=>Transform::rotate(angle),
+                ("rotate",&[angle,x,y])=>Transform([1.0,0.0,0.0,1.0,x,y])
+                    .then(Transform::rotate(angle))
+                    .then(Transform([1.0,0.0,0.0,1.0,-x,-y])),
+                ("skewX",&[angle])=>Transform([1.0,0.0,angle.to_radians().tan(),1.0,0.0,0.0]),
+                ("skewY",&[angle])=>Transform([1.0,angle.to_radians().tan(),0.0,1.0,0.0,0.0]),
+                _=>continue,
+            };
+            transform=transform.then(item);
+        }
+        return transform;
+    }
+    fn rotate(degrees:f32)->Transform {
+        let (sin,cos)=degrees.to_radians().sin_cos();
+        Transform([cos,sin,-sin,cos,0.0,0.0])
+    }
+    /// Maps an `svg` element's `viewBox` onto its `width` and `height`, following
+    /// `preserveAspectRatio`
+    fn view_box(attributes:&Attributes)->Transform {
+        let view_box=attributes.get("viewBox")
+            .map(|view_box|view_box.split(|c:char|c==','||c.is_whitespace())
+                .filter(|arg|!arg.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<f32>,_>>());
+        let [min_x,min_y,width,height]=match view_box {
+            Some(Ok(view_box)) if view_box.len()==4&&view_box[2]>0.0&&view_box[3]>0.0=>[view_box[0],view_box[1],view_box[2],view_box[3]],
+            _=>return Transform::IDENTITY,
+        };
+        let viewport_width=attributes.get("width").and_then(|width|length(width)).unwrap_or(width);
+        let viewport_height=attributes.get("height").and_then(|height|length(height)).unwrap_or(height);
+        let mut scale=Vec2::new(viewport_width/width,viewport_height/height);
+        let aspect=attributes.get("preserveAspectRatio").map_or("xMidYMid meet",|aspect|aspect);
+        let mut aspect=aspect.split_whitespace();
+        let align=aspect.next().unwrap_or("xMidYMid");
+        if align=="none" {
+            return Transform([scale.x,0.0,0.0,scale.y,-min_x*scale.x,-min_y*scale.y]);
+        }
+        scale=Vec2::broadcast(if aspect.next()==Some("slice") {
+            scale.reduce_partial_max()
+        } else {
+            scale.reduce_partial_min()
+        });
+        // How far along the leftover space the view box goes
+        let fraction=|min:&str,max:&str|if align.contains(min) {0.0} else if align.contains(max) {1.0} else {0.5};
+        let offset=Vec2::new(
+            (viewport_width-width*scale.x)*fraction("xMin","xMax"),
+            (viewport_height-height*scale.y)*fraction("YMin","YMax"),
+        );
+        return Transform([scale.x,0.0,0.0,scale.y,offset.x-min_x*scale.x,offset.y-min_y*scale.y]);
+    }
+}
+
+/// Parses an SVG length like `1in` into pixels. `None` for percentages and anything unknown.
+fn length(length:&str)->Option<f32> {
+    let length=length.trim();
+    let split=length.find(|c:char|c.is_ascii_alphabetic()||c=='%').unwrap_or(length.len());
+    let (number,unit)=length.split_at(split);
+    let per_unit=match unit {
+        ""|"px"=>1.0,
+        "in"=>96.0,
+        "cm"=>96.0/2.54,
+        "mm"=>96.0/25.4,
+        "pt"=>4.0/3.0,
+        "pc"=>16.0,
+        _=>return None,
+    };
+    return number.parse::<f32>().ok().map(|number|number*per_unit);
+}
+
+
+/// Follows the commands of one path, adding its segments to a line list. The cursor and control
+/// points are in the path's own coordinates, and only the finished lines are transformed.
+struct PathFlattener<'a> {
+    lines:&'a mut Vec<Vec2>,
+    /// Tolerance in the path's own coordinates
+    tolerance:f32,
+    transform:Transform,
+    cursor:Vec2,
+    /// Start of the current subpath, which `Z`, the next `M` and the end of the path go back to
+    start:Vec2,
+    /// Second control point of the last segment if it was a cubic curve, for `S`
+    last_cubic:Option<Vec2>,
+    /// Control point of the last segment if it was a quadratic curve, for `T`
+    last_quadratic:Option<Vec2>,
+}
+impl<'a> PathFlattener<'a> {
+    fn new(lines:&'a mut Vec<Vec2>,tolerance:f32,transform:Transform)->PathFlattener<'a> {
+        PathFlattener {
+            lines,
+            tolerance:tolerance/transform.scale(),
+            transform,
+            cursor:Vec2::zero(),
+            start:Vec2::zero(),
+            last_cubic:None,
+            last_quadratic:None,
+        }
+    }
+    fn command(&mut self,command:&Command) {
+        match command {
+            Command::Move(pos,params)=>{
+                // Pairs after the first are lines
+                for (i,p) in params.chunks_exact(2).enumerate() {
+                    let to=self.point(pos,p[0],p[1]);
+                    if i==0 {
+                        self.close();
+                        self.cursor=to;
+                        self.start=to;
+                        self.last_cubic=None;
+                        self.last_quadratic=None;
+                    } else {
+                        self.line_to(to);
+                    }
+                }
+            },
+            Command::Line(pos,params)=>{
+                for p in params.chunks_exact(2) {
+                    let to=self.point(pos,p[0],p[1]);
+                    self.line_to(to);
+                }
+            },
+            Command::HorizontalLine(pos,params)=>{
+                for &x in params.iter() {
+                    let to=match pos {
+                        Position::Absolute=>Vec2::new(x,self.cursor.y),
+                        Position::Relative=>self.cursor+Vec2::new(x,0.0),
+                    };
+                    self.line_to(to);
+                }
+            },
+            Command::VerticalLine(pos,params)=>{
+                for &y in params.iter() {
+                    let to=match pos {
+                        Position::Absolute=>Vec2::new(self.cursor.x,y),
+                        Position::Relative=>self.cursor+Vec2::new(0.0,y),
+                    };
+                    self.line_to(to);
+                }
+            },
+            Command::QuadraticCurve(pos,params)=>{
+                for p in params.chunks_exact(4) {
+                    let control=self.point(pos,p[0],p[1]);
+                    let to=self.point(pos,p[2],p[3]);
+                    self.quadratic_to(control,to);
+                }
+            },
+            Command::SmoothQuadraticCurve(pos,params)=>{
+                for p in params.chunks_exact(2) {
+                    let control=self.reflected(self.last_quadratic);
+                    let to=self.point(pos,p[0],p[1]);
+                    self.quadratic_to(control,to);
+                }
+            },
+            Command::CubicCurve(pos,params)=>{
+                for p in params.chunks_exact(6) {
+                    let control1=self.point(pos,p[0],p[1]);
+                    let control2=self.point(pos,p[2],p[3]);
+                    let to=self.point(pos,p[4],p[5]);
+                    self.cubic_to(control1,control2,to);
+                }
+            },
+            Command::SmoothCubicCurve(pos,params)=>{
+                for p in params.chunks_exact(4) {
+                    let control1=self.reflected(self.last_cubic);
+                    let control2=self.point(pos,p[0],p[1]);
+                    let to=self.point(pos,p[2],p[3]);
+                    self.cubic_to(control1,control2,to);
+                }
+            },
+            Command::EllipticalArc(pos,params)=>{
+                for p in params.chunks_exact(7) {
+                    let to=self.point(pos,p[5],p[6]);
+                    self.arc_to(Vec2::new(p[0],p[1]),p[2].to_radians(),p[3]!=0.0,p[4]!=0.0,to);
+                }
+            },
+            Command::Close=>self.close(),
+        }
+    }
+    /// Goes back to the start of the subpath. Every subpath gets closed, even without a `Z`, so the
+    /// outline has an inside for collisions.
+    fn close(&mut self) {
+        let start=self.start;
+        self.line_to(start);
+    }
+    fn point(&self,pos:&Position,x:f32,y:f32)->Vec2 {
+        match pos {
+            Position::Absolute=>Vec2::new(x,y),
+            Position::Relative=>self.cursor+Vec2::new(x,y),
+        }
+    }
+    /// The last control point mirrored through the cursor, or the cursor if there isn't one
+    fn reflected(&self,control:Option<Vec2>)->Vec2 {
+        control.map_or(self.cursor,|control|self.cursor*2.0-control)
+    }
+    fn line_to(&mut self,to:Vec2) {
+        if to!=self.cursor {
+            self.lines.push(self.transform.apply(self.cursor));
+            self.lines.push(self.transform.apply(to));
+        }
+        self.cursor=to;
+        self.last_cubic=None;
+        self.last_quadratic=None;
+    }
+    fn quadratic_to(&mut self,control:Vec2,to:Vec2) {
+        let from=self.cursor;
+        // A chord over 1/n of the curve is off by at most |p0-2p1+p2|/(4n²)
+        let bend=(from-control*2.0+to).magnitude();
+        let segments=self.segments(bend/4.0);
+        for i in 1..=segments {
+            let t=i as f32/segments as f32;
+            let point=from*(1.0-t)*(1.0-t)+control*2.0*(1.0-t)*t+to*t*t;
+            self.line_to(point);
+        }
+        self.last_quadratic=Some(control);
+    }
+    fn cubic_to(&mut self,control1:Vec2,control2:Vec2,to:Vec2) {
+        let from=self.cursor;
+        // A chord over 1/n of the curve is off by at most 3/4 of the larger second difference over n²
+        let bend=(from-control1*2.0+control2).magnitude()
+            .max((control1-control2*2.0+to).magnitude());
+        let segments=self.segments(bend*0.75);
+        for i in 1..=segments {
+            let t=i as f32/segments as f32;
+            let s=1.0-t;
+            let point=from*s*s*s+control1*3.0*s*s*t+control2*3.0*s*t*t+to*t*t*t;
+            self.line_to(point);
+        }
+        self.last_cubic=Some(control2);
+    }
+    /// Segments needed for a curve whose chord error over the whole curve is `error`, so the
+    /// error per segment is within the tolerance, up to `MAX_CURVE_SEGMENTS`
+    fn segments(&self,error:f32)->usize {
+        ((error/self.tolerance).sqrt().ceil() as usize).clamp(1,MAX_CURVE_SEGMENTS)
+    }
+    /// Draws an elliptical arc, following the SVG spec's conversion from endpoints to a center
+    fn arc_to(&mut self,radii:Vec2,rotation:f32,large_arc:bool,sweep:bool,to:Vec2) {
+        let from=self.cursor;
+        if from==to {
+            return;
+        }
+        let mut radii=Vec2::new(radii.x.abs(),radii.y.abs());
+        if radii.x==0.0||radii.y==0.0 {
+            self.line_to(to);
+            return;
+        }
+        let (sin,cos)=rotation.sin_cos();
+        let rotate=|v:Vec2|Vec2::new(cos*v.x-sin*v.y,sin*v.x+cos*v.y);
+        let unrotate=|v:Vec2|Vec2::new(cos*v.x+sin*v.y,-sin*v.x+cos*v.y);
+        let half=unrotate((from-to)/2.0);
+        // Radii too small to reach are scaled up until they just do
+        let reach=(half.x/radii.x).powi(2)+(half.y/radii.y).powi(2);
+        if reach>1.0 {
+            radii*=reach.sqrt();
+        }
+        let (rx2,ry2)=(radii.x*radii.x,radii.y*radii.y);
+        let numerator=(rx2*ry2-rx2*half.y*half.y-ry2*half.x*half.x).max(0.0);
+        let k=(numerator/(rx2*half.y*half.y+ry2*half.x*half.x)).sqrt();
+        let k=if large_arc==sweep {-k} else {k};
+        let center=Vec2::new(k*radii.x*half.y/radii.y,-k*radii.y*half.x/radii.x);
+        let u=(half-center)/radii;
+        let v=(-half-center)/radii;
+        let start_angle=u.y.atan2(u.x);
+        let mut sweep_angle=(u.x*v.y-u.y*v.x).atan2(u.dot(v));
+        if !sweep&&sweep_angle>0.0 {
+            sweep_angle-=TAU;
+        } else if sweep&&sweep_angle<0.0 {
+            sweep_angle+=TAU;
+        }
+        let center=rotate(center)+(from+to)/2.0;
+        // Largest angle a chord of the larger radius can span within the tolerance
+        let radius=radii.x.max(radii.y);
+        let step=if self.tolerance<radius {
+            2.0*(1.0-self.tolerance/radius).acos()
+        } else {
+            FRAC_PI_2
+        };
+        let segments=((sweep_angle.abs()/step).ceil() as usize).clamp(1,MAX_CURVE_SEGMENTS);
+        for i in 1..segments {
+            let angle=start_angle+sweep_angle*i as f32/segments as f32;
+            let point=rotate(Vec2::new(radii.x*angle.cos(),radii.y*angle.sin()))+center;
+            self.line_to(point);
+        }
+        self.line_to(to);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn svg(path:&str)->String {
+        format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><path d="{}"/></svg>"#,path)
+    }
+
+    /// How far outside a circle of `radius` the furthest and closest vertices are
+    fn radial_error(lines:&[Vec2],center:Vec2,radius:f32)->(f32,f32) {
+        lines.iter()
+            .map(|p|(*p-center).magnitude()-radius)
+            .fold((f32::MIN,f32::MAX),|(max,min),e|(max.max(e),min.min(e)))
+    }
+
+    #[test]
+    fn arcs_stay_on_the_circle() {
+        let mut lines=Vec::new();
+        let mut path=PathFlattener::new(&mut lines,0.1,Transform::IDENTITY);
+        for command in Data::parse("M 0 50 A 50 50 0 1 1 100 50 A 50 50 0 1 1 0 50").unwrap().iter() {
+            path.command(command);
+        }
+        let (max,min)=radial_error(&lines,Vec2::new(50.0,50.0),50.0);
+        assert!(max<1e-3&&min>-0.1-1e-3);
+        // A chord can sag the whole tolerance, so a full circle needs at least this many
+        assert!(lines.len()/2>=(std::f32::consts::PI/(1.0-0.1/50.0_f32).acos()).floor() as usize);
+    }
+
+    #[test]
+    fn curves_are_within_the_tolerance() {
+        let mut lines=Vec::new();
+        let mut path=PathFlattener::new(&mut lines,0.5,Transform::IDENTITY);
+        // The parabola y=x²/100
+        for command in Data::parse("M -100 100 Q 0 -100 100 100").unwrap().iter() {
+            path.command(command);
+        }
+        for segment in lines.chunks(2) {
+            let mid=(segment[0]+segment[1])/2.0;
+            let exact=mid.x*mid.x/100.0;
+            assert!((mid.y-exact).abs()<=0.5+1e-3);
         }
-        if first.is_some() {
-            vector.push(first.unwrap());
-            vector.push(cursor);
+    }
+
+    #[test]
+    fn tiny_tolerances_are_capped() {
+        let mut lines=Vec::new();
+        let mut path=PathFlattener::new(&mut lines,1e-12,Transform::IDENTITY);
+        for command in Data::parse("M 0 0 C 0 100 100 100 100 0 A 50 50 0 0 1 0 0").unwrap().iter() {
+            path.command(command);
+        }
+        assert!(lines.len()/2<=2*MAX_CURVE_SEGMENTS);
+    }
+
+    fn attributes(pairs:&[(&str,&str)])->Attributes {
+        pairs.iter()
+            .map(|&(name,value)|(name.to_string(),value.into()))
+            .collect()
+    }
+
+    fn close_to(a:Vec2,b:Vec2)->bool {
+        (a-b).magnitude()<1e-3
+    }
+
+    /// Width and height of the box around every vertex
+    fn bounds(lines:&[Vec2])->Vec2 {
+        let min=lines.iter().copied().fold(Vec2::broadcast(f32::MAX),Vec2::partial_min);
+        let max=lines.iter().copied().fold(Vec2::broadcast(f32::MIN),Vec2::partial_max);
+        return max-min;
+    }
+
+    #[test]
+    fn nested_transforms_apply_innermost_first() {
+        // Rotated first the 10x1 bar stands up and is then widened to 2x10. The other way around it
+        // would be 1x20.
+        let source=r#"<svg xmlns="http://www.w3.org/2000/svg"><g transform="scale(2 1)"><path transform="rotate(90)" d="M 0 0 H 10 V 1 H 0 Z"/></g></svg>"#;
+        let size=bounds(&svg_to_vector(source,0.5).unwrap());
+        assert!((size.y/size.x-5.0).abs()<1e-3);
+    }
+
+    #[test]
+    fn transform_lists_apply_right_to_left() {
+        let transform=Transform::parse("translate(10 0) scale(2)");
+        assert!(close_to(transform.apply(Vec2::new(1.0,1.0)),Vec2::new(12.0,2.0)));
+    }
+
+    #[test]
+    fn rotations_can_be_about_a_point() {
+        let transform=Transform::parse("rotate(90 10 0)");
+        assert!(close_to(transform.apply(Vec2::new(10.0,0.0)),Vec2::new(10.0,0.0)));
+        assert!(close_to(transform.apply(Vec2::new(20.0,0.0)),Vec2::new(10.0,10.0)));
+    }
+
+    #[test]
+    fn view_boxes_meet_by_default() {
+        let transform=Transform::view_box(&attributes(&[("width","200"),("height","100"),("viewBox","0 0 100 100")]));
+        assert!(close_to(transform.apply(Vec2::new(0.0,0.0)),Vec2::new(50.0,0.0)));
+        assert!(close_to(transform.apply(Vec2::new(100.0,100.0)),Vec2::new(150.0,100.0)));
+        let transform=Transform::view_box(&attributes(&[
+            ("width","200"),
+            ("height","100"),
+            ("viewBox","0 0 100 100"),
+            ("preserveAspectRatio","xMinYMin meet"),
+        ]));
+        assert!(close_to(transform.apply(Vec2::new(0.0,0.0)),Vec2::new(0.0,0.0)));
+    }
+
+    #[test]
+    fn view_boxes_can_slice_or_stretch() {
+        let transform=Transform::view_box(&attributes(&[
+            ("width","200"),
+            ("height","100"),
+            ("viewBox","0 0 100 100"),
+            ("preserveAspectRatio","xMidYMid slice"),
+        ]));
+        assert!(close_to(transform.apply(Vec2::new(0.0,0.0)),Vec2::new(0.0,-50.0)));
+        assert!(close_to(transform.apply(Vec2::new(100.0,100.0)),Vec2::new(200.0,150.0)));
+        let transform=Transform::view_box(&attributes(&[
+            ("width","200"),
+            ("height","100"),
+            ("viewBox","50 0 100 100"),
+            ("preserveAspectRatio","none"),
+        ]));
+        assert!(close_to(transform.apply(Vec2::new(50.0,0.0)),Vec2::new(0.0,0.0)));
+        assert!(close_to(transform.apply(Vec2::new(150.0,100.0)),Vec2::new(200.0,100.0)));
+    }
+
+    #[test]
+    fn lengths_are_converted_to_pixels() {
+        assert_eq!(length("10"),Some(10.0));
+        assert_eq!(length("10px"),Some(10.0));
+        assert_eq!(length("1in"),Some(96.0));
+        assert!((length("2.54cm").unwrap()-96.0).abs()<1e-3);
+        assert!((length("12pt").unwrap()-16.0).abs()<1e-3);
+        assert_eq!(length("50%"),None);
+        assert_eq!(length("1em"),None);
+        // Like the bundled asteroids: a 500 unit view box on a 1 inch canvas
+        let transform=Transform::view_box(&attributes(&[("width","1in"),("height","1in"),("viewBox","0 0 500 500")]));
+        assert!(close_to(transform.apply(Vec2::new(500.0,500.0)),Vec2::new(96.0,96.0)));
+    }
+
+    #[test]
+    fn normalized_shapes_are_centered_with_a_radius_of_1() {
+        let mut lines=vec![
+            Vec2::new(10.0,10.0),Vec2::new(30.0,10.0),
+            Vec2::new(30.0,10.0),Vec2::new(30.0,30.0),
+            Vec2::new(30.0,30.0),Vec2::new(10.0,30.0),
+            Vec2::new(10.0,30.0),Vec2::new(10.0,10.0),
+        ];
+        normalize(&mut lines).unwrap();
+        let radius=1.0/2.0_f32.sqrt();
+        assert!(close_to(lines[0],Vec2::new(-radius,-radius)));
+        assert!(close_to(lines[2],Vec2::new(radius,-radius)));
+        assert!(lines.iter().all(|vtx|(vtx.magnitude()-1.0).abs()<1e-5));
+        assert!(normalize(&mut [Vec2::new(1.0,1.0),Vec2::new(1.0,1.0)]).is_none());
+    }
+
+    #[test]
+    fn open_subpaths_are_closed() {
+        let lines=svg_to_vector(&svg("M 0 0 L 100 0 L 50 100 M 0 50 L 10 50 L 10 60"),0.5).unwrap();
+        // Two triangles, 3 lines each
+        assert_eq!(lines.len(),12);
+        for triangle in lines.chunks(6) {
+            assert_eq!(triangle[0],triangle[5]);
         }
-        assert!(vector.len()%2==0);
-        Some(vector)
-    } else {
-        None
     }
 }
@@ -0,0 +1,73 @@
+//! Waves of asteroids. Clearing the field starts the next wave, picked from a difficulty curve in
+//! the config.
+
+
+use std::ops::Range;
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wave {
+    /// Large asteroids spawned at the start of the wave
+    pub asteroids:usize,
+    /// Range of each velocity component of the spawned asteroids
+    pub speed:Range<f32>,
+}
+
+
+/// Settings for wave `number`, counting from 1. Waves past the end of the curve keep going from
+/// the last one, adding an asteroid and 10% speed each.
+pub fn wave_settings(curve:&[Wave],number:u32)->Wave {
+    let idx=(number.max(1)-1) as usize;
+    let last=match curve.last() {
+        Some(last)=>last,
+        None=>return Wave {asteroids:1,speed:50.0..200.0},
+    };
+    if idx<curve.len() {
+        return curve[idx].clone();
+    }
+    let extra=idx+1-curve.len();
+    let factor=1.0+0.1*extra as f32;
+    return Wave {
+        asteroids:last.asteroids+extra,
+        speed:(last.speed.start*factor)..(last.speed.end*factor),
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve()->Vec<Wave> {
+        vec![
+            Wave {asteroids:4,speed:50.0..100.0},
+            Wave {asteroids:6,speed:60.0..120.0},
+        ]
+    }
+
+    #[test]
+    fn waves_on_the_curve_are_used_as_is() {
+        assert_eq!(wave_settings(&curve(),1).asteroids,4);
+        assert_eq!(wave_settings(&curve(),2).asteroids,6);
+        // Wave 0 is treated as the first
+        assert_eq!(wave_settings(&curve(),0).asteroids,4);
+    }
+
+    #[test]
+    fn waves_past_the_curve_keep_getting_harder() {
+        let wave=wave_settings(&curve(),4);
+        assert_eq!(wave.asteroids,8);
+        assert!((wave.speed.start-72.0).abs()<1e-4);
+        assert!((wave.speed.end-144.0).abs()<1e-4);
+    }
+
+    #[test]
+    fn an_empty_curve_still_has_waves() {
+        assert_eq!(wave_settings(&[],3).asteroids,1);
+    }
+}